@@ -0,0 +1,134 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use lecar::controller::Controller;
+use std::time::{Duration, Instant};
+
+/// Main cache sizes benchmarked, the policy caches are sized to the main cache as in the paper
+const SIZES: [usize; 4] = [1_000, 10_000, 100_000, 1_000_000];
+
+/// Instantiates a controller with a full main cache holding the keys 0..size
+/// The policy caches start empty
+fn filled(size: usize) -> Controller {
+    let mut controller = Controller::new(size, size, size).unwrap();
+    for key in 0..size {
+        controller.insert(&key.to_string(), key as u32);
+    }
+
+    controller
+}
+
+fn bench_get_hit(c: &mut Criterion) {
+    let mut group = c.benchmark_group("get_hit");
+    group.sample_size(10);
+
+    for size in SIZES {
+        let mut controller = filled(size);
+        let keys: Vec<String> = (0..size).map(|key| key.to_string()).collect();
+        let mut next = 0;
+
+        group.bench_with_input(BenchmarkId::from_parameter(size), &size, |b, _| {
+            b.iter(|| {
+                next = (next + 1) % keys.len();
+                black_box(controller.get(&keys[next]))
+            })
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_get_miss(c: &mut Criterion) {
+    let mut group = c.benchmark_group("get_miss");
+    group.sample_size(10);
+
+    for size in SIZES {
+        let mut controller = filled(size);
+        let missing = size.to_string();
+
+        group.bench_with_input(BenchmarkId::from_parameter(size), &size, |b, _| {
+            b.iter(|| black_box(controller.get(&missing)))
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_history_hit(c: &mut Criterion) {
+    let mut group = c.benchmark_group("history_hit");
+    group.sample_size(10);
+
+    for size in SIZES {
+        group.bench_with_input(BenchmarkId::from_parameter(size), &size, |b, &size| {
+            b.iter_custom(|iters| {
+                // Evict up to `size` keys into the policy caches, which are large enough to keep all of them
+                let capped = (iters as usize).min(size);
+                let mut controller = filled(size);
+                for key in size..size + capped {
+                    controller.insert(&key.to_string(), key as u32);
+                }
+                let evicted: Vec<String> = (0..size + capped)
+                    .map(|key| key.to_string())
+                    .filter(|key| controller.get_index_of(key).is_none())
+                    .collect();
+
+                // Every promotion evicts another key into a policy cache, so the remaining keys stay there
+                let start = Instant::now();
+                for key in &evicted {
+                    black_box(controller.get(key));
+                }
+                let elapsed = start.elapsed();
+
+                // Scale the measured hits up to the `iters` criterion asked for
+                match evicted.len() {
+                    0 => Duration::ZERO,
+                    n => elapsed.mul_f64(iters as f64 / n as f64)
+                }
+            })
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_insert_with_eviction(c: &mut Criterion) {
+    let mut group = c.benchmark_group("insert_with_eviction");
+    group.sample_size(10);
+
+    for size in SIZES {
+        let mut controller = filled(size);
+        let mut next = size;
+
+        group.bench_with_input(BenchmarkId::from_parameter(size), &size, |b, _| {
+            b.iter(|| {
+                next += 1;
+                controller.insert(&next.to_string(), next as u32)
+            })
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_snapshot(c: &mut Criterion) {
+    let mut group = c.benchmark_group("snapshot_serialize");
+    group.sample_size(10);
+
+    for size in SIZES {
+        let controller = filled(size);
+
+        group.bench_with_input(BenchmarkId::from_parameter(size), &size, |b, _| {
+            b.iter(|| black_box(serde_json::to_vec(&controller).unwrap()))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_get_hit,
+    bench_get_miss,
+    bench_history_hit,
+    bench_insert_with_eviction,
+    bench_snapshot
+);
+criterion_main!(benches);
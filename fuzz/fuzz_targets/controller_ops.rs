@@ -0,0 +1,52 @@
+#![no_main]
+
+use arbitrary::Arbitrary;
+use lecar::controller::Controller;
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Arbitrary, Debug)]
+enum Operation {
+    Get(u8),
+    Insert(u8, u32),
+    Remove(u8),
+    Snapshot
+}
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    cache_size: u8,
+    lfu_cache_size: u8,
+    lru_cache_size: u8,
+    operations: Vec<Operation>
+}
+
+fuzz_target!(|input: Input| {
+    let mut controller = match Controller::new(
+        input.cache_size as usize,
+        input.lfu_cache_size as usize,
+        input.lru_cache_size as usize
+    ) {
+        Ok(controller) => controller,
+        Err(_) => return
+    };
+
+    for operation in input.operations {
+        match operation {
+            Operation::Get(key) => {
+                controller.get(&key.to_string());
+            },
+            Operation::Insert(key, value) => controller.insert(&key.to_string(), value),
+            Operation::Remove(key) => {
+                controller.remove(&key.to_string());
+            },
+            Operation::Snapshot => {
+                let snapshot = controller.to_snapshot().expect("snapshot of a valid controller");
+                controller = Controller::from_snapshot(&snapshot).expect("restore of a valid snapshot");
+            }
+        }
+
+        if let Err(violation) = controller.check_invariants() {
+            panic!("{}", violation);
+        }
+    }
+});
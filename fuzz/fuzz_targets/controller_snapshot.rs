@@ -0,0 +1,19 @@
+#![no_main]
+
+use lecar::controller::Controller;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    // Untrusted snapshots must be rejected with an error, never panic
+    if let Ok(mut controller) = Controller::from_snapshot(data) {
+        for key in 0..8u32 {
+            controller.get(&key.to_string());
+            controller.insert(&key.to_string(), key);
+        }
+
+        if let Err(violation) = controller.check_invariants() {
+            panic!("{}", violation);
+        }
+        let _ = controller.to_snapshot();
+    }
+});
@@ -0,0 +1,55 @@
+use crate::sketch::{CountMinSketch, Doorkeeper};
+use serde::{Deserialize, Serialize};
+
+/// TinyLFU admission filter (Einziger, Friedman and Manes)
+/// The first occurrence of a key only reaches the doorkeeper, later ones are counted by the sketch
+/// Both are aged together once the number of recorded occurrences reaches the sample size
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TinyLfu {
+    sketch: CountMinSketch,
+    doorkeeper: Doorkeeper,
+    additions: usize,
+    sample_size: usize
+}
+
+impl TinyLfu {
+    /// Instantiates a new filter in front of a cache of the given capacity
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            sketch: CountMinSketch::with_sample_size(capacity, usize::MAX),
+            doorkeeper: Doorkeeper::new(capacity),
+            additions: 0,
+            sample_size: capacity.max(1) * 10
+        }
+    }
+
+    /// Records an occurrence of the key
+    pub fn record(&mut self, key: &str) {
+        if self.doorkeeper.insert(key) {
+            self.sketch.increment(key);
+        }
+
+        self.additions = self.additions.saturating_add(1);
+        if self.additions >= self.sample_size {
+            self.sketch.reset();
+            self.doorkeeper.clear();
+            self.additions /= 2;
+        }
+    }
+
+    /// Estimates how often the key was seen
+    pub fn estimate(&self, key: &str) -> u8 {
+        self.sketch.estimate(key) + self.doorkeeper.contains(key) as u8
+    }
+
+    /// Verifies the sketch and the doorkeeper can be indexed by any key
+    pub fn check_invariants(&self) -> Result<(), String> {
+        self.sketch.check_invariants()?;
+        self.doorkeeper.check_invariants()
+    }
+
+    /// Returns whether the candidate is estimated to be more popular than the victim it would replace
+    pub fn admit(&self, candidate: &str, victim: &str) -> bool {
+        self.estimate(candidate) > self.estimate(victim)
+    }
+}
@@ -0,0 +1,128 @@
+use crate::baselines::list::KeyList;
+use crate::cache::{ICache, IReplacementCache};
+use std::collections::HashMap;
+
+/// Adaptive replacement cache (Megiddo and Modha)
+/// Balances a recency list `t1` and a frequency list `t2` using the ghost lists `b1` and `b2`
+#[derive(Clone, Debug)]
+pub struct Arc {
+    capacity: usize,
+    target: usize,
+    values: HashMap<String, u32>,
+    t1: KeyList,
+    t2: KeyList,
+    b1: KeyList,
+    b2: KeyList
+}
+
+impl Arc {
+    /// Moves a resident key out of `t1` or `t2` into the matching ghost list
+    fn replace(&mut self, in_b2: bool) {
+        let from_t1 = !self.t1.is_empty()
+            && ((in_b2 && self.t1.len() == self.target) || self.t1.len() > self.target || self.t2.is_empty());
+
+        let (resident, ghost) = match from_t1 {
+            true => (&mut self.t1, &mut self.b1),
+            false => (&mut self.t2, &mut self.b2)
+        };
+
+        if let Some(victim) = resident.pop_front() {
+            self.values.remove(&victim);
+            ghost.push_back(&victim);
+        }
+    }
+}
+
+impl ICache for Arc {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            target: 0,
+            values: HashMap::with_capacity(capacity),
+            t1: KeyList::new(),
+            t2: KeyList::new(),
+            b1: KeyList::new(),
+            b2: KeyList::new()
+        }
+    }
+
+    fn contains(&self, key: &str) -> bool {
+        self.values.contains_key(key)
+    }
+
+    fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    fn full(&self) -> bool {
+        self.values.len() >= self.capacity
+    }
+}
+
+impl IReplacementCache for Arc {
+    fn name(&self) -> &str {
+        "ARC"
+    }
+
+    fn get(&mut self, key: &str) -> Option<u32> {
+        let value = *self.values.get(key)?;
+        self.t1.remove(key);
+        self.t2.push_back(key);
+
+        Some(value)
+    }
+
+    fn insert(&mut self, key: &str, value: u32) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        if self.values.contains_key(key) {
+            self.values.insert(key.to_string(), value);
+            self.t1.remove(key);
+            self.t2.push_back(key);
+            return;
+        }
+
+        if self.b1.contains(key) {
+            let delta = (self.b2.len() / self.b1.len()).max(1);
+            self.target = (self.target + delta).min(self.capacity);
+            self.b1.remove(key);
+            if self.full() {
+                self.replace(false);
+            }
+            self.t2.push_back(key);
+        } else if self.b2.contains(key) {
+            let delta = (self.b1.len() / self.b2.len()).max(1);
+            self.target = self.target.saturating_sub(delta);
+            self.b2.remove(key);
+            if self.full() {
+                self.replace(true);
+            }
+            self.t2.push_back(key);
+        } else {
+            let l1 = self.t1.len() + self.b1.len();
+            let total = l1 + self.t2.len() + self.b2.len();
+            if l1 >= self.capacity {
+                if self.t1.len() < self.capacity {
+                    self.b1.pop_front();
+                    if self.full() {
+                        self.replace(false);
+                    }
+                } else if let Some(victim) = self.t1.pop_front() {
+                    self.values.remove(&victim);
+                }
+            } else if total >= self.capacity {
+                if total >= 2 * self.capacity {
+                    self.b2.pop_front();
+                }
+                if self.full() {
+                    self.replace(false);
+                }
+            }
+            self.t1.push_back(key);
+        }
+
+        self.values.insert(key.to_string(), value);
+    }
+}
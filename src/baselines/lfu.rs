@@ -0,0 +1,83 @@
+use crate::cache::{ICache, IReplacementCache};
+use std::collections::{BTreeSet, HashMap};
+
+/// Least frequently used cache
+/// Ties between equally frequent keys evict the least recently used one
+#[derive(Clone, Debug)]
+pub struct Lfu {
+    capacity: usize,
+    values: HashMap<String, (u32, usize, u64)>,
+    order: BTreeSet<(usize, u64, String)>,
+    clock: u64
+}
+
+impl Lfu {
+    /// Bumps the frequency and recency of a cached key
+    fn touch(&mut self, key: &str) {
+        if let Some((_, frequency, last_used)) = self.values.get_mut(key) {
+            self.order.remove(&(*frequency, *last_used, key.to_string()));
+            *frequency += 1;
+            *last_used = self.clock;
+            self.order.insert((*frequency, *last_used, key.to_string()));
+            self.clock += 1;
+        }
+    }
+}
+
+impl ICache for Lfu {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            values: HashMap::with_capacity(capacity),
+            order: BTreeSet::new(),
+            clock: 0
+        }
+    }
+
+    fn contains(&self, key: &str) -> bool {
+        self.values.contains_key(key)
+    }
+
+    fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    fn full(&self) -> bool {
+        self.values.len() >= self.capacity
+    }
+}
+
+impl IReplacementCache for Lfu {
+    fn name(&self) -> &str {
+        "LFU"
+    }
+
+    fn get(&mut self, key: &str) -> Option<u32> {
+        let value = self.values.get(key)?.0;
+        self.touch(key);
+
+        Some(value)
+    }
+
+    fn insert(&mut self, key: &str, value: u32) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        if let Some(entry) = self.values.get_mut(key) {
+            entry.0 = value;
+            self.touch(key);
+            return;
+        }
+
+        if self.full() {
+            if let Some((_, _, victim)) = self.order.pop_first() {
+                self.values.remove(&victim);
+            }
+        }
+
+        self.values.insert(key.to_string(), (value, 1, self.clock));
+        self.order.insert((1, self.clock, key.to_string()));
+        self.clock += 1;
+    }
+}
@@ -0,0 +1,171 @@
+use crate::baselines::list::KeyList;
+use crate::cache::{ICache, IReplacementCache};
+use std::collections::HashMap;
+
+/// Status of a key tracked by LIRS
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum Status {
+    Lir,
+    ResidentHir,
+    NonResidentHir
+}
+
+/// Low inter-reference recency set cache (Jiang and Zhang)
+/// Keys with a short reuse distance (LIR) stay cached, the rest (HIR) go through a small FIFO queue
+#[derive(Clone, Debug)]
+pub struct Lirs {
+    capacity: usize,
+    lir_capacity: usize,
+    lirs: usize,
+    values: HashMap<String, u32>,
+    status: HashMap<String, Status>,
+    stack: KeyList,
+    queue: KeyList,
+    non_resident: KeyList
+}
+
+impl Lirs {
+    /// Removes HIR keys from the bottom of the stack until a LIR key is at the bottom
+    fn prune(&mut self) {
+        while let Some(bottom) = self.stack.front() {
+            match self.status.get(bottom) {
+                Some(Status::Lir) => break,
+                Some(Status::NonResidentHir) => {
+                    let bottom = bottom.to_string();
+                    self.status.remove(&bottom);
+                    self.non_resident.remove(&bottom);
+                    self.stack.remove(&bottom);
+                },
+                _ => {
+                    self.stack.pop_front();
+                }
+            }
+        }
+    }
+
+    /// Turns the LIR key at the bottom of the stack into a resident HIR key
+    fn demote_bottom(&mut self) {
+        self.prune();
+
+        if let Some(bottom) = self.stack.pop_front() {
+            self.status.insert(bottom.clone(), Status::ResidentHir);
+            self.queue.push_back(&bottom);
+            self.lirs -= 1;
+            self.prune();
+        }
+    }
+
+    /// Promotes a key to LIR, moving it to the top of the stack
+    fn promote(&mut self, key: &str) {
+        self.status.insert(key.to_string(), Status::Lir);
+        self.lirs += 1;
+        self.queue.remove(key);
+        self.non_resident.remove(key);
+        self.stack.push_back(key);
+    }
+
+    /// Updates the stack and queue on a hit
+    fn touch(&mut self, key: &str) {
+        match self.status.get(key) {
+            Some(Status::Lir) => {
+                self.stack.push_back(key);
+                self.prune();
+            },
+            Some(Status::ResidentHir) if self.stack.contains(key) => {
+                self.promote(key);
+                self.demote_bottom();
+            },
+            Some(Status::ResidentHir) => {
+                self.stack.push_back(key);
+                self.queue.push_back(key);
+            },
+            _ => {}
+        }
+    }
+}
+
+impl ICache for Lirs {
+    fn new(capacity: usize) -> Self {
+        let hir_capacity = (capacity / 100).max(1);
+
+        Self {
+            capacity,
+            lir_capacity: capacity.saturating_sub(hir_capacity),
+            lirs: 0,
+            values: HashMap::with_capacity(capacity),
+            status: HashMap::with_capacity(capacity),
+            stack: KeyList::new(),
+            queue: KeyList::new(),
+            non_resident: KeyList::new()
+        }
+    }
+
+    fn contains(&self, key: &str) -> bool {
+        self.values.contains_key(key)
+    }
+
+    fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    fn full(&self) -> bool {
+        self.values.len() >= self.capacity
+    }
+}
+
+impl IReplacementCache for Lirs {
+    fn name(&self) -> &str {
+        "LIRS"
+    }
+
+    fn get(&mut self, key: &str) -> Option<u32> {
+        let value = *self.values.get(key)?;
+        self.touch(key);
+
+        Some(value)
+    }
+
+    fn insert(&mut self, key: &str, value: u32) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        if self.values.contains_key(key) {
+            self.values.insert(key.to_string(), value);
+            self.touch(key);
+            return;
+        }
+
+        if self.full() {
+            if let Some(victim) = self.queue.pop_front() {
+                self.values.remove(&victim);
+                if self.stack.contains(&victim) {
+                    self.status.insert(victim.clone(), Status::NonResidentHir);
+                    self.non_resident.push_back(&victim);
+                } else {
+                    self.status.remove(&victim);
+                }
+            }
+        }
+
+        if self.lirs < self.lir_capacity {
+            self.promote(key);
+        } else if self.status.get(key) == Some(&Status::NonResidentHir) {
+            self.promote(key);
+            self.demote_bottom();
+        } else {
+            self.status.insert(key.to_string(), Status::ResidentHir);
+            self.stack.push_back(key);
+            self.queue.push_back(key);
+        }
+        self.values.insert(key.to_string(), value);
+
+        // Bound the metadata kept for non resident keys
+        while self.non_resident.len() > self.capacity {
+            if let Some(ghost) = self.non_resident.pop_front() {
+                self.stack.remove(&ghost);
+                self.status.remove(&ghost);
+            }
+        }
+    }
+}
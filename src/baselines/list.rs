@@ -0,0 +1,61 @@
+use std::collections::{BTreeMap, HashMap};
+
+/// Ordered set of keys supporting O(log n) removal from anywhere
+/// The front holds the oldest key and the back the newest
+#[derive(Clone, Debug, Default)]
+pub struct KeyList {
+    positions: HashMap<String, u64>,
+    order: BTreeMap<u64, String>,
+    next: u64
+}
+
+impl KeyList {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.positions.contains_key(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Pushes a key to the back, moving it there if it is already present
+    pub fn push_back(&mut self, key: &str) {
+        self.remove(key);
+        self.positions.insert(key.to_string(), self.next);
+        self.order.insert(self.next, key.to_string());
+        self.next += 1;
+    }
+
+    /// Removes a key, returning whether it was present
+    pub fn remove(&mut self, key: &str) -> bool {
+        match self.positions.remove(key) {
+            Some(position) => {
+                self.order.remove(&position);
+                true
+            },
+            None => false
+        }
+    }
+
+    /// Returns the oldest key
+    pub fn front(&self) -> Option<&str> {
+        self.order.values().next().map(|key| key.as_str())
+    }
+
+    /// Removes and returns the oldest key
+    pub fn pop_front(&mut self) -> Option<String> {
+        let position = *self.order.keys().next()?;
+        let key = self.order.remove(&position)?;
+        self.positions.remove(&key);
+
+        Some(key)
+    }
+}
@@ -0,0 +1,61 @@
+use crate::baselines::list::KeyList;
+use crate::cache::{ICache, IReplacementCache};
+use std::collections::HashMap;
+
+/// Least recently used cache
+#[derive(Clone, Debug)]
+pub struct Lru {
+    capacity: usize,
+    values: HashMap<String, u32>,
+    order: KeyList
+}
+
+impl ICache for Lru {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            values: HashMap::with_capacity(capacity),
+            order: KeyList::new()
+        }
+    }
+
+    fn contains(&self, key: &str) -> bool {
+        self.values.contains_key(key)
+    }
+
+    fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    fn full(&self) -> bool {
+        self.values.len() >= self.capacity
+    }
+}
+
+impl IReplacementCache for Lru {
+    fn name(&self) -> &str {
+        "LRU"
+    }
+
+    fn get(&mut self, key: &str) -> Option<u32> {
+        let value = *self.values.get(key)?;
+        self.order.push_back(key);
+
+        Some(value)
+    }
+
+    fn insert(&mut self, key: &str, value: u32) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        if !self.values.contains_key(key) && self.full() {
+            if let Some(victim) = self.order.pop_front() {
+                self.values.remove(&victim);
+            }
+        }
+
+        self.values.insert(key.to_string(), value);
+        self.order.push_back(key);
+    }
+}
@@ -0,0 +1,18 @@
+mod arc;
+mod lfu;
+mod lirs;
+mod list;
+mod lru;
+mod opt;
+mod tiny_lfu;
+mod two_q;
+
+pub use self::{
+    arc::Arc,
+    lfu::Lfu,
+    lirs::Lirs,
+    lru::Lru,
+    opt::Belady,
+    tiny_lfu::WTinyLfu,
+    two_q::TwoQ
+};
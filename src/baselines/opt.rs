@@ -0,0 +1,119 @@
+use crate::cache::{ICache, IReplacementCache};
+use std::collections::{BTreeSet, HashMap, VecDeque};
+
+/// Belady's optimal offline cache
+/// Evicts the key whose next request is the furthest in the future, or skips caching the new key
+/// if it is requested after every cached key
+/// Needs the sequence of requested keys through `prepare`, every `get` and every `insert` not
+/// directly following a miss on the same key counts as one request
+#[derive(Clone, Debug)]
+pub struct Belady {
+    capacity: usize,
+    values: HashMap<String, u32>,
+    future: HashMap<String, VecDeque<usize>>,
+    next_uses: HashMap<String, usize>,
+    order: BTreeSet<(usize, String)>,
+    last_miss: Option<(String, usize)>
+}
+
+impl Belady {
+    /// Consumes the current request of a key, returning the position of its next request
+    fn consume(&mut self, key: &str) -> usize {
+        match self.future.get_mut(key) {
+            Some(positions) => {
+                positions.pop_front();
+                positions.front().copied().unwrap_or(usize::MAX)
+            },
+            None => usize::MAX
+        }
+    }
+
+    /// Records the next request of a cached key
+    fn reschedule(&mut self, key: &str, next_use: usize) {
+        if let Some(previous) = self.next_uses.insert(key.to_string(), next_use) {
+            self.order.remove(&(previous, key.to_string()));
+        }
+        self.order.insert((next_use, key.to_string()));
+    }
+}
+
+impl ICache for Belady {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            values: HashMap::with_capacity(capacity),
+            future: HashMap::new(),
+            next_uses: HashMap::with_capacity(capacity),
+            order: BTreeSet::new(),
+            last_miss: None
+        }
+    }
+
+    fn contains(&self, key: &str) -> bool {
+        self.values.contains_key(key)
+    }
+
+    fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    fn full(&self) -> bool {
+        self.values.len() >= self.capacity
+    }
+}
+
+impl IReplacementCache for Belady {
+    fn name(&self) -> &str {
+        "OPT"
+    }
+
+    fn get(&mut self, key: &str) -> Option<u32> {
+        let next_use = self.consume(key);
+
+        match self.values.get(key).copied() {
+            Some(value) => {
+                self.last_miss = None;
+                self.reschedule(key, next_use);
+                Some(value)
+            },
+            None => {
+                self.last_miss = Some((key.to_string(), next_use));
+                None
+            }
+        }
+    }
+
+    fn insert(&mut self, key: &str, value: u32) {
+        let next_use = match self.last_miss.take() {
+            Some((missed, next_use)) if missed == key => next_use,
+            _ => self.consume(key)
+        };
+
+        if self.values.contains_key(key) {
+            self.values.insert(key.to_string(), value);
+            self.reschedule(key, next_use);
+            return;
+        }
+
+        if self.full() {
+            match self.order.iter().next_back().cloned() {
+                Some((furthest, victim)) if furthest > next_use => {
+                    self.order.remove(&(furthest, victim.clone()));
+                    self.next_uses.remove(&victim);
+                    self.values.remove(&victim);
+                },
+                _ => return
+            }
+        }
+
+        self.values.insert(key.to_string(), value);
+        self.reschedule(key, next_use);
+    }
+
+    fn prepare(&mut self, keys: &[&str]) {
+        self.future.clear();
+        for (position, key) in keys.iter().enumerate() {
+            self.future.entry(key.to_string()).or_default().push_back(position);
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use crate::baselines::list::KeyList;
+use crate::cache::{ICache, IReplacementCache};
+use crate::sketch::CountMinSketch;
+use std::collections::HashMap;
+
+/// Window TinyLFU cache (Einziger, Friedman and Manes)
+/// New keys enter a small LRU window, keys leaving the window are admitted into a segmented LRU
+/// only if the sketch estimates them to be more popular than the segmented LRU's victim
+#[derive(Clone, Debug)]
+pub struct WTinyLfu {
+    capacity: usize,
+    window_capacity: usize,
+    protected_capacity: usize,
+    values: HashMap<String, u32>,
+    window: KeyList,
+    probation: KeyList,
+    protected: KeyList,
+    sketch: CountMinSketch,
+    last_miss: Option<String>
+}
+
+impl WTinyLfu {
+    /// Updates the segments on a hit
+    fn touch(&mut self, key: &str) {
+        if self.window.contains(key) {
+            self.window.push_back(key);
+        } else if self.protected.contains(key) {
+            self.protected.push_back(key);
+        } else if self.probation.remove(key) {
+            self.protected.push_back(key);
+            if self.protected.len() > self.protected_capacity {
+                if let Some(demoted) = self.protected.pop_front() {
+                    self.probation.push_back(&demoted);
+                }
+            }
+        }
+    }
+
+    /// Moves the window's victim into the main segments if the sketch favours it
+    fn evict_window(&mut self) {
+        let candidate = match self.window.pop_front() {
+            Some(candidate) => candidate,
+            None => return
+        };
+
+        let main_capacity = self.capacity - self.window_capacity;
+        if self.probation.len() + self.protected.len() < main_capacity {
+            self.probation.push_back(&candidate);
+            return;
+        }
+
+        let victim = self.probation.front().or_else(|| self.protected.front()).map(|key| key.to_string());
+        match victim {
+            Some(victim) if self.sketch.estimate(&candidate) > self.sketch.estimate(&victim) => {
+                self.probation.remove(&victim);
+                self.protected.remove(&victim);
+                self.values.remove(&victim);
+                self.probation.push_back(&candidate);
+            },
+            _ => {
+                self.values.remove(&candidate);
+            }
+        }
+    }
+}
+
+impl ICache for WTinyLfu {
+    fn new(capacity: usize) -> Self {
+        let window_capacity = (capacity / 100).max(1).min(capacity);
+        let main_capacity = capacity - window_capacity;
+
+        Self {
+            capacity,
+            window_capacity,
+            protected_capacity: main_capacity * 4 / 5,
+            values: HashMap::with_capacity(capacity),
+            window: KeyList::new(),
+            probation: KeyList::new(),
+            protected: KeyList::new(),
+            sketch: CountMinSketch::new(capacity),
+            last_miss: None
+        }
+    }
+
+    fn contains(&self, key: &str) -> bool {
+        self.values.contains_key(key)
+    }
+
+    fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    fn full(&self) -> bool {
+        self.values.len() >= self.capacity
+    }
+}
+
+impl IReplacementCache for WTinyLfu {
+    fn name(&self) -> &str {
+        "W-TinyLFU"
+    }
+
+    fn get(&mut self, key: &str) -> Option<u32> {
+        self.sketch.increment(key);
+
+        match self.values.get(key).copied() {
+            Some(value) => {
+                self.last_miss = None;
+                self.touch(key);
+                Some(value)
+            },
+            None => {
+                self.last_miss = Some(key.to_string());
+                None
+            }
+        }
+    }
+
+    fn insert(&mut self, key: &str, value: u32) {
+        // The request was already counted by the miss preceding this insert
+        if self.last_miss.take().as_deref() != Some(key) {
+            self.sketch.increment(key);
+        }
+
+        if self.capacity == 0 {
+            return;
+        }
+
+        if let Some(cached) = self.values.get_mut(key) {
+            *cached = value;
+            self.touch(key);
+            return;
+        }
+
+        self.values.insert(key.to_string(), value);
+        self.window.push_back(key);
+        if self.window.len() > self.window_capacity {
+            self.evict_window();
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use crate::baselines::list::KeyList;
+use crate::cache::{ICache, IReplacementCache};
+use std::collections::HashMap;
+
+/// Full 2Q cache (Johnson and Shasha)
+/// New keys enter the FIFO `a1_in`, keys evicted from it are remembered in the ghost FIFO `a1_out`
+/// and promoted to the LRU `am` if they are requested again
+#[derive(Clone, Debug)]
+pub struct TwoQ {
+    capacity: usize,
+    in_capacity: usize,
+    out_capacity: usize,
+    values: HashMap<String, u32>,
+    a1_in: KeyList,
+    a1_out: KeyList,
+    am: KeyList
+}
+
+impl TwoQ {
+    /// Frees a slot for a new key if the cache is full
+    fn reclaim(&mut self) {
+        if !self.full() {
+            return;
+        }
+
+        if self.a1_in.len() > self.in_capacity || self.am.is_empty() {
+            if let Some(victim) = self.a1_in.pop_front() {
+                self.values.remove(&victim);
+                self.a1_out.push_back(&victim);
+                if self.a1_out.len() > self.out_capacity {
+                    self.a1_out.pop_front();
+                }
+            }
+        } else if let Some(victim) = self.am.pop_front() {
+            self.values.remove(&victim);
+        }
+    }
+}
+
+impl ICache for TwoQ {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            in_capacity: (capacity / 4).max(1),
+            out_capacity: (capacity / 2).max(1),
+            values: HashMap::with_capacity(capacity),
+            a1_in: KeyList::new(),
+            a1_out: KeyList::new(),
+            am: KeyList::new()
+        }
+    }
+
+    fn contains(&self, key: &str) -> bool {
+        self.values.contains_key(key)
+    }
+
+    fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    fn full(&self) -> bool {
+        self.values.len() >= self.capacity
+    }
+}
+
+impl IReplacementCache for TwoQ {
+    fn name(&self) -> &str {
+        "2Q"
+    }
+
+    fn get(&mut self, key: &str) -> Option<u32> {
+        let value = *self.values.get(key)?;
+        // Hits in a1_in are correlated references and do not change the order
+        if self.am.contains(key) {
+            self.am.push_back(key);
+        }
+
+        Some(value)
+    }
+
+    fn insert(&mut self, key: &str, value: u32) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        if let Some(cached) = self.values.get_mut(key) {
+            *cached = value;
+            if self.am.contains(key) {
+                self.am.push_back(key);
+            }
+            return;
+        }
+
+        self.reclaim();
+        if self.a1_out.remove(key) {
+            self.am.push_back(key);
+        } else {
+            self.a1_in.push_back(key);
+        }
+        self.values.insert(key.to_string(), value);
+    }
+}
@@ -1,16 +1,49 @@
+use crate::cache::FrequencyAging;
+use std::cmp::Ordering;
 use std::time::Instant;
 use serde::{Deserialize, Serialize};
 
 /// Definition of a cache item
-#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
 pub struct CacheItem {
     frequency: usize,
-    #[serde(with = "serde_millis")]
+    #[serde(with = "crate::cache::millis")]
     last_used: Instant,
     key: String,
     value: u32,
+    #[serde(default)]
+    pinned: bool,
+    // Cost of missing the item
+    #[serde(default = "default_cost")]
+    cost: u32,
+    // GreedyDual credit, the inflation of the cache when the item was last used plus its cost
+    #[serde(default)]
+    credit: f64,
+    // Size of the object, in bytes
+    #[serde(default = "default_size")]
+    size: u32,
+    // Uses since frequencies were last aged
+    #[serde(default)]
+    recent: usize,
+    // Sequence number of the last use in the main cache
+    #[serde(default)]
+    used: u64,
+    // Sequence number of the insertion into the main cache
+    #[serde(default)]
+    inserted: u64
 }
 
+fn default_cost() -> u32 {
+    1
+}
+
+fn default_size() -> u32 {
+    1
+}
+
+/// Credits are never NaN
+impl Eq for CacheItem {}
+
 /// Implementation of a cache item
 impl CacheItem {
     /// Instantiates a new cache item given the key and value
@@ -19,17 +52,44 @@ impl CacheItem {
             frequency: 0,
             last_used: Instant::now(),
             key,
-            value
+            value,
+            pinned: false,
+            cost: default_cost(),
+            credit: 0.0,
+            size: default_size(),
+            recent: 0,
+            used: 0,
+            inserted: 0
+        }
+    }
+
+    /// Instantiates a new cache item given the key, value and the cost of missing it
+    pub fn with_cost(key: String, value: u32, cost: u32) -> Self {
+        Self {
+            cost,
+            ..Self::new(key, value)
         }
     }
 
     /// Increments the frequency of the item
     /// Updates the last used time to now
     pub fn touch(&mut self) {
-        self.frequency += 1;
+        self.frequency = self.frequency.saturating_add(1);
+        self.recent = self.recent.saturating_add(1);
         self.last_used = Instant::now();
     }
 
+    /// Ages the frequency, starting a new period
+    pub fn age(&mut self, aging: FrequencyAging) {
+        self.frequency = match aging {
+            FrequencyAging::Off => self.frequency,
+            FrequencyAging::Halving { .. } => self.frequency / 2,
+            FrequencyAging::Decay { factor, .. } => (self.frequency as f64 * factor) as usize,
+            FrequencyAging::Window { .. } => self.recent
+        };
+        self.recent = 0;
+    }
+
     /// Sets the cache item's value to the one given
     /// Calls the touch method
     pub fn update(&mut self, value: u32) {
@@ -47,16 +107,104 @@ impl CacheItem {
         self.last_used
     }
 
+    /// Returns the sequence number of the last use in the main cache
+    pub fn used(&self) -> u64 {
+        self.used
+    }
+
+    /// Records a use in the main cache given its sequence number
+    pub fn set_used(&mut self, sequence: u64) {
+        self.used = sequence;
+    }
+
+    /// Returns the sequence number of the insertion into the main cache
+    pub fn inserted(&self) -> u64 {
+        self.inserted
+    }
+
+    /// Records the insertion into the main cache given its sequence number
+    pub fn set_inserted(&mut self, sequence: u64) {
+        self.inserted = sequence;
+    }
+
+    /// Orders items from the least to the most recently used
+    /// Ties, only possible between items that were never used in a main cache, fall back on the key
+    pub fn cmp_recency(&self, other: &Self) -> Ordering {
+        self.used.cmp(&other.used).then_with(|| self.key.cmp(&other.key))
+    }
+
+    /// Orders items from the least to the most frequently used
+    /// Ties are broken by recency, then by insertion sequence, then by key
+    pub fn cmp_frequency(&self, other: &Self) -> Ordering {
+        self.frequency
+            .cmp(&other.frequency)
+            .then(self.used.cmp(&other.used))
+            .then(self.inserted.cmp(&other.inserted))
+            .then_with(|| self.key.cmp(&other.key))
+    }
+
+    /// Orders items from the lowest to the highest GreedyDual credit
+    /// Ties are broken by recency
+    pub fn cmp_credit(&self, other: &Self) -> Ordering {
+        self.credit.total_cmp(&other.credit).then_with(|| self.cmp_recency(other))
+    }
+
     /// Getter for key
     pub fn key(&self) -> &str {
         &self.key
     }
 
+    /// Getter for pinned
+    pub fn pinned(&self) -> bool {
+        self.pinned
+    }
+
+    /// Setter for pinned
+    /// Pinned items are never ejected from the main cache
+    pub fn set_pinned(&mut self, pinned: bool) {
+        self.pinned = pinned;
+    }
+
+    /// Getter for cost
+    pub fn cost(&self) -> u32 {
+        self.cost
+    }
+
+    /// Setter for cost
+    pub fn set_cost(&mut self, cost: u32) {
+        self.cost = cost;
+    }
+
+    /// Getter for size
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// Setter for size
+    pub fn set_size(&mut self, size: u32) {
+        self.size = size;
+    }
+
+    /// Getter for credit
+    pub fn credit(&self) -> f64 {
+        self.credit
+    }
+
+    /// Resets the GreedyDual credit to the given inflation plus the cost
+    pub fn refresh_credit(&mut self, inflation: f64) {
+        self.credit = inflation + self.cost as f64;
+    }
+
     /// Getter for value
     pub fn value(&self) -> u32 {
         self.value
     }
 
+    /// Mutable getter for value
+    pub fn value_mut(&mut self) -> &mut u32 {
+        &mut self.value
+    }
+
     /// Getter for value
     /// Consumes self
     pub fn value_owned(self) -> u32 {
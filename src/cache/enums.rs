@@ -1,7 +1,11 @@
-#[derive(Copy, Clone)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub enum Policy {
     LFU,
-    LRU
+    LRU,
+    /// GreedyDual, ejects the item with the least credit
+    GDS
 }
 
 impl Policy {
@@ -12,4 +16,30 @@ impl Policy {
             false => Self::LRU
         }
     }
+}
+
+/// How item frequencies fade so that items that were popular long ago do not stay ahead forever
+/// Periods are counted in requests
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Debug, Default)]
+pub enum FrequencyAging {
+    /// Frequencies only grow
+    #[default]
+    Off,
+    /// Halves every frequency once every period
+    Halving { period: u64 },
+    /// Multiplies every frequency by the factor, within [0, 1), once every period
+    Decay { period: u64, factor: f64 },
+    /// Frequencies count the uses of the current and the previous window
+    Window { window: u64 }
+}
+
+impl FrequencyAging {
+    /// Returns the number of requests between two agings
+    pub fn period(&self) -> Option<u64> {
+        match self {
+            Self::Off => None,
+            Self::Halving { period } | Self::Decay { period, .. } => Some(*period),
+            Self::Window { window } => Some(*window)
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,70 @@
+use std::cmp::Ordering;
+use crate::cache::{ICacheItemWrapper, CacheItem, Policy};
+use std::time::Instant;
+use serde::{Deserialize, Serialize};
+
+/// Wrapper struct for CacheItem to implement different PartialEq, PartialOrd, and Ord
+#[derive(Serialize, Deserialize, Eq, Clone, Debug)]
+pub struct GDSCacheItem(CacheItem,
+    #[serde(with = "crate::cache::millis")]
+    Instant,
+    #[serde(default)]
+    u64);
+
+/// Implementation of the GDSCacheItem
+impl GDSCacheItem {
+    /// Returns a wrapped CacheItem
+    /// Records the logical time at which it was ejected from the main cache
+    pub fn new(cache_item: CacheItem, evicted_at: u64) -> Self {
+        Self(cache_item, Instant::now(), evicted_at)
+    }
+}
+
+/// Implements PartialEq for GDS use
+impl PartialEq for GDSCacheItem {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+/// Implements Ord for GDS use
+impl Ord for GDSCacheItem {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.0.cmp_credit(&self.0)
+    }
+}
+
+/// Implements PartialOrd for GDS use
+impl PartialOrd for GDSCacheItem {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Implements ICacheItemWrapper for GDS use
+impl ICacheItemWrapper for GDSCacheItem {
+    fn get_inner_key(&self) -> &str {
+        self.0.key()
+    }
+
+    fn get_duration(&self) -> f64 {
+        self.1.elapsed().as_secs_f64()
+    }
+
+    fn evicted_at(&self) -> u64 {
+        self.2
+    }
+
+    fn get_inner(&self) -> &CacheItem {
+        &self.0
+    }
+
+    fn get_inner_mut(&mut self) -> &mut CacheItem {
+        &mut self.0
+    }
+
+    fn into_inner(self) -> (CacheItem, f64, Policy) {
+        let duration = self.get_duration();
+        (self.0, duration, Policy::GDS)
+    }
+}
@@ -5,29 +5,32 @@ use serde::{Deserialize, Serialize};
 
 /// Wrapper struct for CacheItem to implement different PartialEq, PartialOrd, and Ord
 #[derive(Serialize, Deserialize, Eq, Clone, Debug)]
-pub struct LFUCacheItem(CacheItem, 
-    #[serde(with = "serde_millis")]
-    Instant);
+pub struct LFUCacheItem(CacheItem,
+    #[serde(with = "crate::cache::millis")]
+    Instant,
+    #[serde(default)]
+    u64);
 
 /// Implementation of the LFUCacheItem
 impl LFUCacheItem {
     /// Returns a wrapped CacheItem
-    pub fn new(cache_item: CacheItem) -> Self {
-        Self(cache_item, Instant::now())
+    /// Records the logical time at which it was ejected from the main cache
+    pub fn new(cache_item: CacheItem, evicted_at: u64) -> Self {
+        Self(cache_item, Instant::now(), evicted_at)
     }
 }
 
 /// Implements PartialEq for LFU use
 impl PartialEq for LFUCacheItem {
     fn eq(&self, other: &Self) -> bool {
-        self.0.frequency() == other.0.frequency()
+        self.cmp(other) == Ordering::Equal
     }
 }
 
 /// Implements Ord for LFU use
 impl Ord for LFUCacheItem {
     fn cmp(&self, other: &Self) -> Ordering {
-        other.0.frequency().cmp(&self.0.frequency())
+        other.0.cmp_frequency(&self.0)
     }
 }
 
@@ -48,6 +51,18 @@ impl ICacheItemWrapper for LFUCacheItem {
         self.1.elapsed().as_secs_f64()
     }
 
+    fn evicted_at(&self) -> u64 {
+        self.2
+    }
+
+    fn get_inner(&self) -> &CacheItem {
+        &self.0
+    }
+
+    fn get_inner_mut(&mut self) -> &mut CacheItem {
+        &mut self.0
+    }
+
     fn into_inner(self) -> (CacheItem, f64, Policy) {
         let duration = self.get_duration();
         (self.0, duration, Policy::LFU)
@@ -0,0 +1,41 @@
+use serde::de::Error as DeError;
+use serde::ser::Error as SerError;
+use serde::{Deserialize, Deserializer, Serializer};
+use std::convert::TryFrom;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Serializes an Instant as milliseconds since the Unix epoch
+/// Instants are converted relative to the current time as they have no fixed origin
+pub fn serialize<S: Serializer>(instant: &Instant, serializer: S) -> Result<S::Ok, S::Error> {
+    let now = Instant::now();
+    let system_now = SystemTime::now();
+
+    let system_time = match instant.checked_duration_since(now) {
+        Some(ahead) => system_now.checked_add(ahead),
+        None => system_now.checked_sub(now.duration_since(*instant))
+    };
+
+    system_time
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .and_then(|since_epoch| u64::try_from(since_epoch.as_millis()).ok())
+        .ok_or_else(|| S::Error::custom("instant out of range"))
+        .and_then(|millis| serializer.serialize_u64(millis))
+}
+
+/// Deserializes an Instant from milliseconds since the Unix epoch
+/// Fails instead of panicking when the time cannot be represented as an Instant,
+/// such as times before the machine booted
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Instant, D::Error> {
+    let millis = u64::deserialize(deserializer)?;
+    let now = Instant::now();
+    let system_now = SystemTime::now();
+
+    let instant = UNIX_EPOCH
+        .checked_add(Duration::from_millis(millis))
+        .and_then(|system_time| match system_time.duration_since(system_now) {
+            Ok(ahead) => now.checked_add(ahead),
+            Err(behind) => now.checked_sub(behind.duration())
+        });
+
+    instant.ok_or_else(|| D::Error::custom("instant out of range"))
+}
@@ -1,15 +1,20 @@
-use std::collections::BinaryHeap;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashSet};
+use std::mem;
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
 mod cache_item;
 mod enums;
+mod gds;
 mod lfu;
 mod lru;
+mod millis;
 
 pub use self::{
     cache_item::CacheItem,
-    enums::Policy,
+    enums::{FrequencyAging, Policy},
+    gds::GDSCacheItem,
     lfu::LFUCacheItem,
     lru::LRUCacheItem
 };
@@ -19,6 +24,9 @@ pub use self::{
 pub trait ICacheItemWrapper: Clone + Eq + PartialEq + Ord + PartialOrd {
     fn get_inner_key(&self) -> &str;
     fn get_duration(&self) -> f64;
+    fn evicted_at(&self) -> u64;
+    fn get_inner(&self) -> &CacheItem;
+    fn get_inner_mut(&mut self) -> &mut CacheItem;
     fn into_inner(self) -> (CacheItem, f64, Policy);
 }
 
@@ -28,6 +36,28 @@ pub trait ICache {
     fn contains(&self, key: &str) -> bool;
     fn len(&self) -> usize;
     fn full(&self) -> bool;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Trait for complete key / value caches so that replacement algorithms can be compared head to head
+/// A miss on `get` leaves the cache untouched, the replacement decision happens on the following `insert`
+pub trait IReplacementCache: ICache {
+    fn name(&self) -> &str;
+    fn get(&mut self, key: &str) -> Option<u32>;
+    fn insert(&mut self, key: &str, value: u32);
+
+    /// Inserts an item of the given size
+    /// Only size aware caches make use of the size
+    fn insert_sized(&mut self, key: &str, value: u32, _size: u32) {
+        self.insert(key, value)
+    }
+
+    /// Gives the cache the full sequence of keys that will be requested
+    /// Only offline algorithms make use of it
+    fn prepare(&mut self, _keys: &[&str]) {}
 }
 
 /// Trait to give a policy to a cache
@@ -41,8 +71,22 @@ pub trait IPolicy<I: ICacheItemWrapper> {
 /// Basic struct for caches
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Cache<C> {
-    pub capacity: usize,
-    cache: C
+    capacity: usize,
+    cache: C,
+    // GreedyDual inflation, the credit of the last item ejected through the GDS policy
+    #[serde(default)]
+    inflation: f64,
+    // Sequence number of the last use, orders items deterministically so that replicas fed the same requests stay in lockstep
+    #[serde(default)]
+    sequence: u64
+}
+
+/// Implementation of the capacity shared by every cache
+impl<C> Cache<C> {
+    /// Returns the maximum number of items the cache holds
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
 }
 
 /// Implementation of ICache for a BinaryHeap (priority queue) Cache
@@ -50,7 +94,9 @@ impl<I: ICacheItemWrapper> ICache for Cache<BinaryHeap<I>> {
     fn new(capacity: usize) -> Self {
         Self {
             capacity,
-            cache: BinaryHeap::with_capacity(capacity)
+            cache: BinaryHeap::with_capacity(capacity),
+            inflation: 0.0,
+            sequence: 0
         }
     }
 
@@ -63,7 +109,7 @@ impl<I: ICacheItemWrapper> ICache for Cache<BinaryHeap<I>> {
     }
 
     fn full(&self) -> bool {
-        self.capacity == self.cache.len()
+        self.cache.len() >= self.capacity
     }
 }
 
@@ -72,7 +118,9 @@ impl ICache for Cache<IndexMap<String, CacheItem>> {
     fn new(capacity: usize) -> Self {
         Self {
             capacity,
-            cache: IndexMap::with_capacity(capacity)
+            cache: IndexMap::with_capacity(capacity),
+            inflation: 0.0,
+            sequence: 0
         }
     }
 
@@ -85,7 +133,7 @@ impl ICache for Cache<IndexMap<String, CacheItem>> {
     }
 
     fn full(&self) -> bool {
-        self.capacity == self.cache.len()
+        self.cache.len() >= self.capacity
     }
 }
 
@@ -93,10 +141,94 @@ impl ICache for Cache<IndexMap<String, CacheItem>> {
 impl Cache<IndexMap<String, CacheItem>> {
     /// Retrieves a cached item and updates it before returning it
     pub fn get(&mut self, key: &str) -> Option<&CacheItem> {
-        self.cache.get_mut(key).and_then(|ci| {
-            ci.touch();
-            Some(&*ci)
-        })
+        let item = self.cache.get_mut(key)?;
+        self.sequence = self.sequence.saturating_add(1);
+
+        item.touch();
+        item.set_used(self.sequence);
+        item.refresh_credit(self.inflation);
+        Some(&*item)
+    }
+
+    /// Updates the value of a cached item, counting as a use
+    /// Returns the previous value
+    pub fn update(&mut self, key: &str, value: u32) -> Option<u32> {
+        let item = self.cache.get_mut(key)?;
+        let previous = item.value();
+        self.sequence = self.sequence.saturating_add(1);
+
+        item.update(value);
+        item.set_used(self.sequence);
+        item.refresh_credit(self.inflation);
+        Some(previous)
+    }
+
+    /// Changes the capacity of the cache
+    /// Items over a smaller capacity stay until they are ejected
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+    }
+
+    /// Returns the next sequence number
+    fn next_sequence(&mut self) -> u64 {
+        self.sequence = self.sequence.saturating_add(1);
+        self.sequence
+    }
+
+    /// Stamps an item entering the main cache with the next sequence number
+    /// An item replacing one with the same key keeps its insertion sequence
+    fn stamp(&mut self, item: &mut CacheItem) {
+        let sequence = self.next_sequence();
+        let inserted = self.cache.get(item.key()).map_or(sequence, |existing| existing.inserted());
+
+        item.set_used(sequence);
+        item.set_inserted(inserted);
+    }
+
+    /// Retrieves a cached item without updating it
+    pub fn peek(&self, key: &str) -> Option<&CacheItem> {
+        self.cache.get(key)
+    }
+
+    /// Ages the frequency of every item
+    pub fn age_frequencies(&mut self, aging: FrequencyAging) {
+        self.cache.values_mut().for_each(|item| item.age(aging));
+    }
+
+    /// Retrieves a mutable cached item without updating it
+    pub fn peek_mut(&mut self, key: &str) -> Option<&mut CacheItem> {
+        self.cache.get_mut(key)
+    }
+
+    /// Iterates over the keys in the cache
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.cache.keys().map(|key| key.as_str())
+    }
+
+    /// Iterates over the items in the cache in insertion order
+    pub fn iter(&self) -> impl Iterator<Item = &CacheItem> {
+        self.cache.values()
+    }
+
+    /// Verifies the cache holds no more items than its capacity
+    /// And that every item is stored under its own key
+    pub fn check_invariants(&self) -> Result<(), String> {
+        if self.cache.len() > self.capacity {
+            return Err(format!("main cache holds {} items over its capacity of {}", self.cache.len(), self.capacity));
+        }
+
+        // Credits are totally ordered only when finite
+        if !self.inflation.is_finite() {
+            return Err(format!("main cache has a GreedyDual inflation of {}", self.inflation));
+        }
+        if let Some(item) = self.cache.values().find(|item| !item.credit().is_finite()) {
+            return Err(format!("item `{}` has a credit of {}", item.key(), item.credit()));
+        }
+
+        match self.cache.iter().find(|(key, item)| key.as_str() != item.key()) {
+            Some((key, item)) => Err(format!("item `{}` is stored under key `{}`", item.key(), key)),
+            None => Ok(())
+        }
     }
 
     pub fn get_index_of(&self, key: &str) -> Option<usize> {
@@ -112,15 +244,106 @@ impl Cache<IndexMap<String, CacheItem>> {
         }
     }
 
+    /// Removes an item from the cache, keeping the order of the other items
+    pub fn remove(&mut self, key: &str) -> Option<CacheItem> {
+        self.cache.shift_remove(key)
+    }
+
+    /// Removes every item from the cache, in insertion order
+    /// Resets the GreedyDual inflation
+    pub fn drain(&mut self) -> impl Iterator<Item = CacheItem> + '_ {
+        self.inflation = 0.0;
+        self.cache.drain(..).map(|(_, item)| item)
+    }
+
+    /// Returns the number of pinned items
+    pub fn pinned_len(&self) -> usize {
+        self.cache.values().filter(|item| item.pinned()).count()
+    }
+
+    /// Returns the unpinned item the policy would eject first, in a single pass
+    pub fn victim(&self, policy: Policy) -> Option<&CacheItem> {
+        let compare = Self::policy_ordering(policy);
+
+        self.cache.values().filter(|item| !item.pinned()).min_by(|li, ri| compare(li, ri))
+    }
+
+    /// Ejects the unpinned item the policy would eject first, in a single pass
+    /// An item ejected through the GDS policy raises the inflation to its credit
+    pub fn eject(&mut self, policy: Policy) -> Option<CacheItem> {
+        let key = self.victim(policy)?.key().to_string();
+        let item = self.cache.shift_remove(&key)?;
+        if policy == Policy::GDS {
+            self.inflation = item.credit();
+        }
+
+        Some(item)
+    }
+
+    /// Returns the item at the given index, as given by `get_index_of`
+    pub fn slot_mut(&mut self, index: usize) -> &mut CacheItem {
+        &mut self.cache[index]
+    }
+
+    /// Inserts an item to the cache whether or not it is full
+    /// Replaces an already existing item
+    /// Returns the item in its slot
+    pub fn insert_slot(&mut self, mut new_item: CacheItem) -> &mut CacheItem {
+        self.stamp(&mut new_item);
+        new_item.refresh_credit(self.inflation);
+        let (index, _) = self.cache.insert_full(new_item.key().to_string(), new_item);
+
+        &mut self.cache[index]
+    }
+
+    /// Returns the given number of unpinned items that match the policy, the first one to match first
+    /// Only the selected items are sorted, the others are partitioned out in linear time
+    pub fn victims(&self, count: usize, policy: Policy) -> Vec<&CacheItem> {
+        let compare = Self::policy_ordering(policy);
+        let mut candidates: Vec<&CacheItem> = self.cache.values().filter(|item| !item.pinned()).collect();
+        if count == 0 {
+            return Vec::new();
+        }
+        if count < candidates.len() {
+            candidates.select_nth_unstable_by(count - 1, |li, ri| compare(li, ri));
+            candidates.truncate(count);
+        }
+        candidates.sort_by(|li, ri| compare(li, ri));
+
+        candidates
+    }
+
+    /// Ejects the given number of items that match the policy
+    /// Selects the victims as `victims` does, then splits them from the other items in a single pass over the cache
+    /// Pinned items are never ejected
+    /// Returns the ejected items, the first one to match the policy first
+    pub fn eject_many(&mut self, count: usize, policy: Policy) -> Vec<CacheItem> {
+        let compare = Self::policy_ordering(policy);
+        let victims: HashSet<String> = self.victims(count, policy).iter().map(|item| item.key().to_string()).collect();
+
+        let (mut ejected, kept): (Vec<_>, Vec<_>) = mem::take(&mut self.cache)
+            .into_iter()
+            .partition(|(key, _)| victims.contains(key));
+        self.cache.extend(kept);
+
+        ejected.sort_by(|(_, li), (_, ri)| compare(li, ri));
+        if let (Policy::GDS, Some((_, last))) = (policy, ejected.last()) {
+            self.inflation = last.credit();
+        }
+
+        ejected.into_iter().map(|(_, item)| item).collect()
+    }
+
     /// Inserts an item to the cache
     /// Updates an already existing item
     /// Fails an returns item if the cache is full
-    #[allow(dead_code)]
-    pub fn insert(&mut self, new_item: CacheItem) -> Option<CacheItem> {
+    pub fn insert(&mut self, mut new_item: CacheItem) -> Option<CacheItem> {
         if self.full() && !self.cache.contains_key(new_item.key()) {
             return Some(new_item);
         }
 
+        self.stamp(&mut new_item);
+        new_item.refresh_credit(self.inflation);
         self.cache.insert(new_item.key().to_string(), new_item);
         None
     }
@@ -129,29 +352,75 @@ impl Cache<IndexMap<String, CacheItem>> {
     /// Updates an already existing item
     /// If the cache is full, given a policy, ejects an item that matches the policy
     pub fn insert_with_policy(&mut self, new_item: CacheItem, policy: Policy) -> Option<CacheItem> {
+        self.insert_ejecting(new_item, policy, Self::policy_ordering(policy))
+    }
+
+    /// Orders items so that the first one is the one the policy ejects
+    /// Ties are broken by sequence numbers so that the choice never depends on the layout of the cache
+    fn policy_ordering(policy: Policy) -> impl Fn(&CacheItem, &CacheItem) -> Ordering {
+        move |li, ri| {
+            match policy {
+                Policy::LFU => li.cmp_frequency(ri),
+                Policy::LRU => li.cmp_recency(ri),
+                Policy::GDS => li.cmp_credit(ri)
+            }
+        }
+    }
+
+    /// Same as `insert_with_policy` but ejects the least recently used of the least frequently used items
+    /// As done by the published LeCaR algorithm
+    pub fn insert_with_paper_policy(&mut self, new_item: CacheItem, policy: Policy) -> Option<CacheItem> {
+        self.insert_ejecting(new_item, policy, |li, ri| {
+            match policy {
+                Policy::LFU => li.frequency().cmp(&ri.frequency()).then(li.used().cmp(&ri.used())),
+                Policy::LRU => li.used().cmp(&ri.used()),
+                Policy::GDS => li.credit().total_cmp(&ri.credit()).then(li.used().cmp(&ri.used()))
+            }
+        })
+    }
+
+    /// Inserts an item to the cache
+    /// Updates an already existing item
+    /// If the cache is full, ejects the first unpinned item given the ordering
+    /// An item ejected through the GDS policy raises the inflation to its credit
+    fn insert_ejecting<F: Fn(&CacheItem, &CacheItem) -> Ordering>(&mut self, mut new_item: CacheItem, policy: Policy, compare: F) -> Option<CacheItem> {
+        let inflation = self.inflation;
+        let sequence = self.next_sequence();
+
         match self.cache.get_mut(new_item.key()) {
             Some(item) => {
+                item.set_cost(new_item.cost());
+                item.set_size(new_item.size());
                 item.update(new_item.value_owned());
+                item.set_used(sequence);
+                item.refresh_credit(inflation);
                 None
             },
             None => {
+                new_item.set_used(sequence);
+                new_item.set_inserted(sequence);
+                new_item.refresh_credit(inflation);
                 if self.capacity > self.cache.len() {
                     self.cache.insert(new_item.key().to_string(), new_item);
                     None
                 } else {
                     let item_to_remove = self.cache
                         .iter()
-                        .min_by(|(_lk, li), (_rk, ri)| {
-                            match policy {
-                                Policy::LFU => li.frequency().cmp(&ri.frequency()),
-                                Policy::LRU => li.last_used().cmp(&ri.last_used())
-                            }
-                        })
-                        .unwrap()
-                        .0
-                        .to_string();
-
-                    let lfu_item = Some(self.cache.remove(&item_to_remove).unwrap());
+                        .filter(|(_, item)| !item.pinned())
+                        .min_by(|(_lk, li), (_rk, ri)| compare(li, ri))
+                        .map(|(key, _)| key.to_string());
+
+                    // A cache without capacity or only holding pinned items ejects the new item straight away
+                    let item_to_remove = match item_to_remove {
+                        Some(key) => key,
+                        None => return Some(new_item)
+                    };
+
+                    let lfu_item = self.cache.remove(&item_to_remove);
+                    if let (Policy::GDS, Some(item)) = (policy, &lfu_item) {
+                        self.inflation = item.credit();
+                    }
+                    new_item.refresh_credit(self.inflation);
                     self.cache.insert(new_item.key().to_string(), new_item);
 
                     lfu_item
@@ -161,6 +430,106 @@ impl Cache<IndexMap<String, CacheItem>> {
     }
 }
 
+/// Implementation of a policy cache backed by a BinaryHeap (priority queue)
+impl<I: ICacheItemWrapper> Cache<BinaryHeap<I>> {
+    /// Iterates over the keys in the cache in arbitrary order
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.cache.iter().map(|item| item.get_inner_key())
+    }
+
+    /// Ages the frequency of every item, reordering the cache
+    pub fn age_frequencies(&mut self, aging: FrequencyAging) {
+        let mut items = mem::take(&mut self.cache).into_vec();
+        items.iter_mut().for_each(|item| item.get_inner_mut().age(aging));
+        self.cache = BinaryHeap::from(items);
+    }
+
+    /// Removes every item from the cache, in the order `eject` would remove them
+    pub fn drain(&mut self) -> Vec<I> {
+        let mut items = mem::take(&mut self.cache).into_sorted_vec();
+        items.reverse();
+
+        items
+    }
+
+    /// Returns the items in the cache in the order `eject` would remove them
+    pub fn items_in_eviction_order(&self) -> Vec<&I> {
+        let mut items: Vec<&I> = self.cache.iter().collect();
+        items.sort_by(|l, r| r.cmp(l));
+
+        items
+    }
+
+    /// Finds an item given a key without ejecting it
+    pub fn peek(&self, key: &str) -> Option<&I> {
+        self.cache.iter().find(|item| item.get_inner_key() == key)
+    }
+
+    /// Changes the capacity of the cache, ejecting items in order until it fits
+    pub fn resize(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.cache.len() > capacity {
+            self.eject();
+        }
+    }
+
+    /// Changes the capacity of the cache, ejecting the items that entered the cache first until it fits
+    pub fn resize_fifo(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        if self.cache.len() > capacity {
+            let mut items = mem::take(&mut self.cache).into_vec();
+            items.sort_by_key(|item| std::cmp::Reverse(item.evicted_at()));
+            items.truncate(capacity);
+            self.cache = BinaryHeap::from(items);
+        }
+    }
+
+    /// If the cache is full, ejects the item that entered the cache first
+    /// Then inserts the given item into the cache
+    pub fn insert_fifo(&mut self, cache_item: I) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        if self.full() {
+            let oldest = self.cache
+                .iter()
+                .min_by_key(|item| item.evicted_at())
+                .map(|item| item.get_inner_key().to_string());
+
+            if let Some(oldest) = oldest {
+                self.cache.retain(|item| item.get_inner_key() != oldest);
+            }
+        }
+
+        self.cache.push(cache_item);
+    }
+
+    /// Verifies the cache holds no more items than its capacity, holds every key once
+    /// And that the next item to be ejected is ordered before every other item
+    pub fn check_invariants(&self) -> Result<(), String> {
+        if self.cache.len() > self.capacity {
+            return Err(format!("policy cache holds {} items over its capacity of {}", self.cache.len(), self.capacity));
+        }
+
+        let mut seen = HashSet::with_capacity(self.cache.len());
+        if let Some(key) = self.keys().find(|key| !seen.insert(*key)) {
+            return Err(format!("policy cache holds key `{}` more than once", key));
+        }
+
+        if let Some(item) = self.cache.iter().find(|item| !item.get_inner().credit().is_finite()) {
+            return Err(format!("policy cache item `{}` has a credit of {}", item.get_inner_key(), item.get_inner().credit()));
+        }
+
+        match self.cache.peek() {
+            Some(top) if self.cache.iter().any(|item| item > top) => {
+                Err(format!("policy cache would eject `{}` before an item ordered ahead of it", top.get_inner_key()))
+            },
+            _ => Ok(())
+        }
+    }
+}
+
 /// Implementation of IPolicy for BinaryHeap (priority queue) caches
 /// The ordering of the BinaryHeap depends on the generic I item
 impl<I: ICacheItemWrapper> IPolicy<I> for Cache<BinaryHeap<I>> {
@@ -172,7 +541,7 @@ impl<I: ICacheItemWrapper> IPolicy<I> for Cache<BinaryHeap<I>> {
     /// Iterates over the BinaryHeap to find an item given a key
     /// If found, ejects, reorders BinaryHeap, and returns the item
     fn maybe_eject_key(&mut self, key: &str) -> Option<I> {
-        match self.cache.iter().filter(|item| item.get_inner_key() == key).next() {
+        match self.cache.iter().find(|item| item.get_inner_key() == key) {
             Some(t) => {
                 let cloned_item = t.clone();
                 self.cache.retain(|item| item.get_inner_key() != key);
@@ -185,6 +554,10 @@ impl<I: ICacheItemWrapper> IPolicy<I> for Cache<BinaryHeap<I>> {
     /// If the cache is full, eject an item from the cache
     /// Then insert the given item into the cache
     fn insert(&mut self, cache_item: I) {
+        if self.capacity == 0 {
+            return;
+        }
+
         if self.full() {
             self.eject();
         }
@@ -0,0 +1,205 @@
+use super::Weights;
+use super::constants::{DISCOUNT_RATE, LEARNING_RATE, MAX_HISTORY_RATIO, MAX_LEARNING_RATE, MIN_HISTORY_RATIO, PAPER_DISCOUNT_BASE, PAPER_LEARNING_RATE};
+use crate::cache::FrequencyAging;
+use crate::error::LecarError;
+use serde::{Deserialize, Serialize};
+
+/// How closely the controller follows the published LeCaR algorithm
+#[derive(Serialize, Deserialize, Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub enum Mode {
+    /// Rewards decay with the seconds an item spent in a policy cache
+    /// Policy caches keep values which `get` returns, and weights are rounded to two decimals
+    #[default]
+    Classic,
+    /// Algorithm 1 of Vietri et al.
+    /// Rewards decay with the number of requests since the eviction and weights keep full precision
+    /// Policy caches only remember keys, so finding a key there is a miss and the insert that follows updates the weights
+    /// The learner is only consulted when the main cache has to eject an item
+    /// And a full policy cache ejects the item that entered it first
+    PaperFaithful
+}
+
+/// What the learner optimizes
+#[derive(Serialize, Deserialize, Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub enum Objective {
+    /// Every hit is worth the same
+    #[default]
+    HitRatio,
+    /// Hits are worth the size of the object, evicting large objects that return is penalized more
+    ByteHitRatio
+}
+
+/// Configuration of a Controller
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ControllerConfig {
+    pub cache_size: usize,
+    pub lfu_cache_size: usize,
+    pub lru_cache_size: usize,
+    /// Size of the GDS policy cache, the cost aware GreedyDual expert is only consulted if it has a capacity
+    #[serde(default)]
+    pub gds_cache_size: usize,
+    pub mode: Mode,
+    #[serde(default)]
+    pub objective: Objective,
+    /// Whether a TinyLFU filter decides if new keys may replace the main cache's victim, not part of the paper
+    #[serde(default)]
+    pub admission: bool,
+    /// How frequencies in the main cache and the policy caches fade, not part of the paper
+    #[serde(default)]
+    pub frequency_aging: FrequencyAging,
+    /// Whether the policy cache sizes and the discount rate are derived from the main cache size, as in the paper
+    /// The configured values are then ignored
+    #[serde(default)]
+    pub auto_size: bool,
+    /// Weights to start from instead of equal ones, such as those saved by a previous run
+    #[serde(default)]
+    pub weights: Option<Weights>,
+    /// Smallest weight of every expert in use, so that the learner keeps exploring the experts it lost faith in
+    /// 0 lets a weight collapse for good, as in the paper
+    #[serde(default)]
+    pub exploration: f64,
+    /// Whether classic mode keeps the weights at full precision instead of rounding them to two decimals
+    #[serde(default)]
+    pub full_precision: bool,
+    /// Number of weight updates between two renormalizations of the weights, which wipe out accumulated rounding errors
+    /// 0 never renormalizes
+    #[serde(default)]
+    pub renormalize_every: u64,
+    pub learning_rate: f64,
+    pub discount_rate: f64
+}
+
+impl ControllerConfig {
+    /// Instantiates the configuration used by `Controller::new`
+    pub fn new(cache_size: usize, lfu_cache_size: usize, lru_cache_size: usize) -> Self {
+        Self {
+            cache_size,
+            lfu_cache_size,
+            lru_cache_size,
+            gds_cache_size: 0,
+            mode: Mode::Classic,
+            objective: Objective::HitRatio,
+            admission: false,
+            frequency_aging: FrequencyAging::Off,
+            auto_size: false,
+            weights: None,
+            exploration: 0.0,
+            full_precision: false,
+            renormalize_every: 0,
+            learning_rate: LEARNING_RATE,
+            discount_rate: DISCOUNT_RATE
+        }
+    }
+
+    /// Instantiates the configuration of the paper for a main cache of the given size
+    /// Both policy caches are as large as the main cache, the learning rate is 0.45 and the discount rate 0.005^(1/N)
+    pub fn paper(cache_size: usize) -> Self {
+        Self {
+            cache_size,
+            lfu_cache_size: cache_size,
+            lru_cache_size: cache_size,
+            gds_cache_size: 0,
+            mode: Mode::PaperFaithful,
+            objective: Objective::HitRatio,
+            admission: false,
+            frequency_aging: FrequencyAging::Off,
+            auto_size: false,
+            weights: None,
+            exploration: 0.0,
+            full_precision: false,
+            renormalize_every: 0,
+            learning_rate: PAPER_LEARNING_RATE,
+            discount_rate: PAPER_DISCOUNT_BASE.powf(1.0 / cache_size as f64)
+        }
+    }
+
+    /// Instantiates a configuration which derives everything else from the size of the main cache
+    /// See `derived`
+    pub fn auto(cache_size: usize) -> Self {
+        Self {
+            auto_size: true,
+            ..Self::new(cache_size, cache_size, cache_size)
+        }.derived()
+    }
+
+    /// Returns the configuration with the derived sizes applied if `auto_size` is set
+    /// Every policy cache in use is as large as the main cache and the discount rate is 0.005^(1/N)
+    pub fn derived(&self) -> Self {
+        if !self.auto_size {
+            return self.clone();
+        }
+
+        Self {
+            lfu_cache_size: self.cache_size,
+            lru_cache_size: self.cache_size,
+            gds_cache_size: match self.gds_cache_size {
+                0 => 0,
+                _ => self.cache_size
+            },
+            discount_rate: PAPER_DISCOUNT_BASE.powf(1.0 / self.cache_size.max(1) as f64),
+            ..self.clone()
+        }
+    }
+
+    /// Lists the policy caches whose size is unusual compared to the main cache
+    /// Such configurations work, but the learner is either slow to notice its mistakes or remembers them for too long
+    pub fn warnings(&self) -> Vec<String> {
+        let config = self.derived();
+        let main = config.cache_size.max(1) as f64;
+
+        [("LFU", config.lfu_cache_size), ("LRU", config.lru_cache_size), ("GDS", config.gds_cache_size)]
+            .iter()
+            .filter(|(name, size)| *name != "GDS" || *size > 0)
+            .filter(|(_, size)| !(MIN_HISTORY_RATIO..=MAX_HISTORY_RATIO).contains(&(*size as f64 / main)))
+            .map(|(name, size)| format!(
+                "the {} policy cache holds {} items for a main cache of {}, outside of the usual {} to {} times",
+                name, size, config.cache_size, MIN_HISTORY_RATIO, MAX_HISTORY_RATIO
+            ))
+            .collect()
+    }
+
+    /// Verifies the configuration describes a working cache
+    /// The main cache needs room for at least one item, the learning rate must be within [0, 100]
+    /// And the discount rate must be within (0, 1]
+    pub fn validate(&self) -> Result<(), LecarError> {
+        if self.cache_size == 0 {
+            return Err(LecarError::InvalidConfig("the main cache needs a capacity of at least 1".to_string()));
+        }
+
+        if !(0.0..=MAX_LEARNING_RATE).contains(&self.learning_rate) {
+            return Err(LecarError::InvalidConfig(format!("learning rate {} is outside of [0, {}]", self.learning_rate, MAX_LEARNING_RATE)));
+        }
+
+        if !(self.discount_rate > 0.0 && self.discount_rate <= 1.0) {
+            return Err(LecarError::InvalidConfig(format!("discount rate {} is outside of (0, 1]", self.discount_rate)));
+        }
+
+        if self.frequency_aging.period() == Some(0) {
+            return Err(LecarError::InvalidConfig("frequency aging needs a period of at least one request".to_string()));
+        }
+
+        if let FrequencyAging::Decay { factor, .. } = self.frequency_aging {
+            if !(0.0..1.0).contains(&factor) {
+                return Err(LecarError::InvalidConfig(format!("decay factor {} is outside of [0, 1)", factor)));
+            }
+        }
+
+        if let Some(weights) = self.weights {
+            weights.validate()?;
+            if weights.gds > 0.0 && self.gds_cache_size == 0 {
+                return Err(LecarError::InvalidConfig("the GDS expert has a weight but no policy cache".to_string()));
+            }
+        }
+
+        let experts = if self.gds_cache_size > 0 { 3.0 } else { 2.0 };
+        if !(0.0..=1.0 / experts).contains(&self.exploration) {
+            return Err(LecarError::InvalidConfig(format!("exploration {} is outside of [0, 1/{}]", self.exploration, experts)));
+        }
+
+        if self.admission && self.mode == Mode::PaperFaithful {
+            return Err(LecarError::InvalidConfig("admission is not part of the paper faithful mode".to_string()));
+        }
+
+        Ok(())
+    }
+}
@@ -1,2 +1,16 @@
 pub const DISCOUNT_RATE: f64 = 0.9738562370164787;  //   0.75 -> 0.005**(1 / self.cache_size)
-pub const LEARNING_RATE: f64 = 0.75;   // 1.5 -> 0.74
\ No newline at end of file
+pub const LEARNING_RATE: f64 = 0.75;   // 1.5 -> 0.74
+
+// Values used by Vietri et al., the discount rate is PAPER_DISCOUNT_BASE^(1 / cache_size)
+pub const PAPER_DISCOUNT_BASE: f64 = 0.005;
+pub const PAPER_LEARNING_RATE: f64 = 0.45;
+
+// Largest learning rate accepted, rewards are at most 1 so that e^(learning rate * reward) stays finite
+pub const MAX_LEARNING_RATE: f64 = 100.0;
+
+// Range of policy cache to main cache size ratios outside of which a configuration is reported as unusual
+pub const MIN_HISTORY_RATIO: f64 = 0.5;
+pub const MAX_HISTORY_RATIO: f64 = 2.0;
+
+// Largest distance from 1 accepted for the sum of the weights, weights within it are normalized before being used
+pub const WEIGHT_TOLERANCE: f64 = 1e-6;
@@ -0,0 +1,105 @@
+use super::Controller;
+use crate::cache::CacheItem;
+
+/// View into a single key of the controller, obtained from `Controller::entry`
+pub enum Entry<'a> {
+    Occupied(OccupiedEntry<'a>),
+    Vacant(VacantEntry<'a>)
+}
+
+/// Entry of a key living in the main cache
+pub struct OccupiedEntry<'a> {
+    item: &'a mut CacheItem
+}
+
+/// Entry of a key missing from the main cache
+pub struct VacantEntry<'a> {
+    controller: &'a mut Controller,
+    key: String
+}
+
+impl<'a> Entry<'a> {
+    /// Returns the entry's key
+    pub fn key(&self) -> &str {
+        match self {
+            Self::Occupied(entry) => entry.key(),
+            Self::Vacant(entry) => entry.key()
+        }
+    }
+
+    /// Inserts the default if vacant
+    /// Returns a mutable reference to the value
+    pub fn or_insert(self, default: u32) -> &'a mut u32 {
+        match self {
+            Self::Occupied(entry) => entry.into_mut(),
+            Self::Vacant(entry) => entry.insert(default)
+        }
+    }
+
+    /// Inserts the result of the given function if vacant
+    /// Returns a mutable reference to the value
+    pub fn or_insert_with<F: FnOnce() -> u32>(self, default: F) -> &'a mut u32 {
+        match self {
+            Self::Occupied(entry) => entry.into_mut(),
+            Self::Vacant(entry) => entry.insert(default())
+        }
+    }
+
+    /// Modifies the value in place if occupied
+    pub fn and_modify<F: FnOnce(&mut u32)>(mut self, f: F) -> Self {
+        if let Self::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+
+        self
+    }
+}
+
+impl<'a> OccupiedEntry<'a> {
+    pub(super) fn new(item: &'a mut CacheItem) -> Self {
+        Self { item }
+    }
+
+    /// Returns the entry's key
+    pub fn key(&self) -> &str {
+        self.item.key()
+    }
+
+    /// Returns the value
+    pub fn get(&self) -> u32 {
+        self.item.value()
+    }
+
+    /// Returns a mutable reference to the value
+    pub fn get_mut(&mut self) -> &mut u32 {
+        self.item.value_mut()
+    }
+
+    /// Converts the entry into a mutable reference to the value bound to the controller's lifetime
+    pub fn into_mut(self) -> &'a mut u32 {
+        self.item.value_mut()
+    }
+
+    /// Sets the value, returning the old one
+    pub fn insert(&mut self, value: u32) -> u32 {
+        std::mem::replace(self.get_mut(), value)
+    }
+}
+
+impl<'a> VacantEntry<'a> {
+    pub(super) fn new(controller: &'a mut Controller, key: String) -> Self {
+        Self { controller, key }
+    }
+
+    /// Returns the entry's key
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Inserts the value, as `Controller::insert` would
+    /// The admission filter is bypassed as the value has to live in the main cache
+    /// Returns a mutable reference to it
+    pub fn insert(self, value: u32) -> &'a mut u32 {
+        self.controller.insert_vacant(&self.key, value)
+    }
+}
@@ -0,0 +1,45 @@
+use super::Location;
+use std::fmt;
+
+/// Reason an item left the controller, as told to the eviction listener
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Removal {
+    /// Ejected from the main cache into the policy cache of the policy drawn by the learner
+    Evicted,
+    /// Dropped by `clear`
+    Cleared,
+    /// Yielded by `drain`
+    Drained,
+    /// Removed by `remove`
+    Removed
+}
+
+/// Callback told of the key, value and cache of every item leaving the controller
+pub type EvictionListener = Box<dyn FnMut(&str, u32, Location, Removal) + Send>;
+
+/// Optional eviction listener, never serialized
+#[derive(Default)]
+pub(super) struct Listener(Option<EvictionListener>);
+
+impl Listener {
+    /// Replaces the callback
+    pub(super) fn set(&mut self, listener: Option<EvictionListener>) {
+        self.0 = listener;
+    }
+
+    /// Tells the callback, if any, that an item left the controller
+    pub(super) fn notify(&mut self, key: &str, value: u32, location: Location, removal: Removal) {
+        if let Some(listener) = &mut self.0 {
+            listener(key, value, location, removal);
+        }
+    }
+}
+
+impl fmt::Debug for Listener {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Some(_) => f.write_str("Some(EvictionListener)"),
+            None => f.write_str("None")
+        }
+    }
+}
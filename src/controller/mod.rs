@@ -1,16 +1,30 @@
-use crate::cache::{Cache, CacheItem, LFUCacheItem, LRUCacheItem, ICache, IPolicy, ICacheItemWrapper, Policy};
-use self::constants::{DISCOUNT_RATE, LEARNING_RATE};
+use crate::admission::TinyLfu;
+use crate::error::LecarError;
+use crate::cache::{Cache, CacheItem, FrequencyAging, GDSCacheItem, LFUCacheItem, LRUCacheItem, ICache, IPolicy, ICacheItemWrapper, IReplacementCache, Policy};
+use self::constants::{DISCOUNT_RATE, LEARNING_RATE, WEIGHT_TOLERANCE};
 use rand::RngCore;
 use rand_chacha::rand_core::SeedableRng;
 use rand_chacha::ChaCha8Rng;
 use std::default::Default;
-use std::collections::BinaryHeap;
+use std::collections::{BinaryHeap, HashSet};
 use indexmap::IndexMap;
 use std::f64::consts::E;
 use std::io::Write;
 use std::fs::OpenOptions;
 
+mod config;
 mod constants;
+mod entry;
+mod listener;
+mod options;
+mod weights;
+
+pub use self::config::{ControllerConfig, Mode, Objective};
+pub use self::entry::{Entry, OccupiedEntry, VacantEntry};
+pub use self::listener::{EvictionListener, Removal};
+use self::listener::Listener;
+pub use self::options::InsertOptions;
+pub use self::weights::Weights;
 
 use serde::{Deserialize, Serialize};
 
@@ -35,29 +49,70 @@ pub struct Counter {
 
     // Memory
     pub memory_size: u64,
+
+    // Bytes
+    #[serde(default)]
+    pub hit_bytes: u64,
+    #[serde(default)]
+    pub missed_bytes: u64,
+
+    // Admission
+    #[serde(default)]
+    pub admitted: u64,
+    #[serde(default)]
+    pub rejected: u64,
+
+    // Maintenance
+    #[serde(default)]
+    pub cleared: u64,
+    #[serde(default)]
+    pub drained: u64,
+    #[serde(default)]
+    pub learner_resets: u64,
 }
 
 impl std::fmt::Display for Counter {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "{},{},{},{},{},{},{},{},{},{},{}, {}\n", self.size, self.num_queries, self.hits, self.misses, 
-            self.raw_messsages_size, self.compressed_size, self.raw_len, self.encoded_len, self.compression_time, self.decompression_time, self.updating_time, self.memory_size)
+        writeln!(f, "{},{},{},{},{},{},{},{},{},{},{}, {},{},{},{},{},{},{},{}", self.size, self.num_queries, self.hits, self.misses, 
+            self.raw_messsages_size, self.compressed_size, self.raw_len, self.encoded_len, self.compression_time, self.decompression_time, self.updating_time, self.memory_size,
+            self.hit_bytes, self.missed_bytes, self.admitted, self.rejected, self.cleared, self.drained, self.learner_resets)
     }
 }
 
 impl Counter {
-    pub fn try_write_to_file(&mut self, path: &str) {
-        if self.num_queries < 1000 { return; }
+    /// Appends the counters to the given file and resets them once at least 1000 queries were counted
+    /// The counters are kept if the file cannot be written
+    pub fn try_write_to_file(&mut self, path: &str) -> Result<(), LecarError> {
+        if self.num_queries < 1000 { return Ok(()); }
 
         let output_str = format!("{}", self);
         let mut f = OpenOptions::new()
             .append(true)
             .create(true) // Optionally create the file if it doesn't already exist
-            .open(path)
-            .expect("Unable to create file");
+            .open(path)?;
 
-        f.write_all(output_str.as_bytes()).expect("Unable to write data");
+        f.write_all(output_str.as_bytes())?;
         
         self.reset();
+        Ok(())
+    }
+
+    /// Records a request for an object of the given size
+    /// The controller records the bytes of its hits, and of its misses once the missed object is inserted
+    /// Misses that are never inserted are up to the caller to record
+    pub fn record_bytes(&mut self, size: u64, hit: bool) {
+        match hit {
+            true => self.hit_bytes = self.hit_bytes.saturating_add(size),
+            false => self.missed_bytes = self.missed_bytes.saturating_add(size)
+        }
+    }
+
+    /// Fraction of the requested bytes that hit, 0 if no bytes were recorded
+    pub fn byte_hit_ratio(&self) -> f64 {
+        match self.hit_bytes + self.missed_bytes {
+            0 => 0.0,
+            bytes => self.hit_bytes as f64 / bytes as f64
+        }
     }
 
     pub fn reset(&mut self) {
@@ -72,35 +127,237 @@ impl Counter {
         self.decompression_time = 0;
         self.updating_time = 0;
         self.memory_size = 0;
+        self.hit_bytes = 0;
+        self.missed_bytes = 0;
+        self.admitted = 0;
+        self.rejected = 0;
+        self.cleared = 0;
+        self.drained = 0;
+        self.learner_resets = 0;
     }
 }
 
+/// Cache in which a key lives
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Location {
+    Main,
+    History(Policy)
+}
+
+/// Outcome of a lookup
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum GetResult {
+    /// The item was found in the main cache
+    MainHit(u32),
+    /// The item was found in a policy cache and moved back into the main cache
+    /// The regret is the reward given to the other policy for the mistake of the given policy
+    HistoryHit { value: u32, policy: Policy, regret: f64 },
+    Miss
+}
+
+impl GetResult {
+    /// Returns the value of a hit
+    pub fn value(&self) -> Option<u32> {
+        match self {
+            Self::MainHit(value) | Self::HistoryHit { value, .. } => Some(*value),
+            Self::Miss => None
+        }
+    }
+}
+
+/// Item remembered by a policy cache
+#[derive(Clone, PartialEq, Debug)]
+pub struct HistoryEntry<'a> {
+    pub key: &'a str,
+    pub value: u32,
+    pub frequency: usize,
+    /// Time spent in the policy cache, in seconds or in requests in paper faithful mode
+    pub age: f64
+}
+
 /// Controlling struct for the cache
 /// Keeps a main cache and several (2+) policy caches
 /// Uses a learner to determine which policy cache to utilize
 /// TODO: Allow custom policy injection
-/// TODO: Allow deserialization for weights probability
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Controller {
     cache: Cache<IndexMap<String, CacheItem>>,
     lfu: Cache<BinaryHeap<LFUCacheItem>>,
     lru: Cache<BinaryHeap<LRUCacheItem>>,
+    #[serde(default = "empty_policy_cache")]
+    gds: Cache<BinaryHeap<GDSCacheItem>>,
     lfu_prob: f64,
+    // Probability of ejecting through the GDS policy, zero unless the cost aware expert is enabled
+    #[serde(default)]
+    gds_prob: f64,
+    // Whether the learner keeps the weights as they are
+    #[serde(default)]
+    weights_frozen: bool,
+    // Smallest weight of every expert in use
+    #[serde(default)]
+    exploration: f64,
+    // Whether classic mode keeps the weights at full precision
+    #[serde(default)]
+    full_precision: bool,
+    // Number of weight updates between two renormalizations, 0 never renormalizes
+    #[serde(default)]
+    renormalize_every: u64,
+    #[serde(default)]
+    weight_updates: u64,
+    // Largest cost inserted, rewards are scaled by the cost of the missed item relative to it
+    #[serde(default = "default_max_cost")]
+    max_cost: u32,
+    #[serde(default)]
+    objective: Objective,
+    // Largest size inserted, rewards are scaled by the size of the missed item relative to it when optimizing bytes
+    #[serde(default = "default_max_size")]
+    max_size: u32,
+    #[serde(default)]
+    admission: Option<TinyLfu>,
+    #[serde(default)]
+    frequency_aging: FrequencyAging,
     rng: ChaCha8Rng,
-    pub counter: Counter
+    pub counter: Counter,
+    #[serde(default)]
+    mode: Mode,
+    #[serde(default = "default_learning_rate")]
+    learning_rate: f64,
+    #[serde(default = "default_discount_rate")]
+    discount_rate: f64,
+    // Logical time, counted in requests
+    #[serde(default)]
+    clock: u64,
+    // Key of the last get that missed, so that the insert that follows is not counted as another request
+    // Nor recorded twice by the admission filter
+    #[serde(skip)]
+    last_miss: Option<String>,
+    #[serde(skip)]
+    listener: Listener
+}
+
+fn empty_policy_cache() -> Cache<BinaryHeap<GDSCacheItem>> {
+    Cache::new(0)
+}
+
+fn default_max_cost() -> u32 {
+    1
+}
+
+fn default_max_size() -> u32 {
+    1
+}
+
+fn default_learning_rate() -> f64 {
+    LEARNING_RATE
+}
+
+fn default_discount_rate() -> f64 {
+    DISCOUNT_RATE
 }
 
 impl Controller {
     /// Instantiates new new Controller given the cache sizes for each cache
-    pub fn new(cache_size: usize, lfu_cache_size: usize, lru_cache_size: usize) -> Self {
-        Self {
-            cache: Cache::new(cache_size),
-            lfu: Cache::new(lfu_cache_size),
-            lru: Cache::new(lru_cache_size),
-            lfu_prob: 0.5,
+    /// Fails if the main cache has no capacity
+    pub fn new(cache_size: usize, lfu_cache_size: usize, lru_cache_size: usize) -> Result<Self, LecarError> {
+        Self::with_config(ControllerConfig::new(cache_size, lfu_cache_size, lru_cache_size))
+    }
+
+    /// Instantiates a new Controller whose policy caches and discount rate are derived from the main cache size
+    /// Fails if the main cache has no capacity
+    pub fn with_cache_size(cache_size: usize) -> Result<Self, LecarError> {
+        Self::with_config(ControllerConfig::auto(cache_size))
+    }
+
+    /// Instantiates a new Controller given its configuration
+    /// Experts start with equal weights
+    /// Fails if the configuration does not validate
+    /// Unusual policy cache sizes do not fail, callers check them with `warnings`
+    pub fn with_config(config: ControllerConfig) -> Result<Self, LecarError> {
+        let config = config.derived();
+        config.validate()?;
+
+        let (lfu_prob, gds_prob) = match config.weights {
+            Some(weights) => {
+                let weights = weights.normalized();
+                (weights.lfu, weights.gds)
+            },
+            None => Self::initial_weights(config.gds_cache_size)
+        };
+
+        Ok(Self {
+            cache: Cache::new(config.cache_size),
+            lfu: Cache::new(config.lfu_cache_size),
+            lru: Cache::new(config.lru_cache_size),
+            gds: Cache::new(config.gds_cache_size),
+            lfu_prob,
+            gds_prob,
+            weights_frozen: false,
+            exploration: config.exploration,
+            full_precision: config.full_precision,
+            renormalize_every: config.renormalize_every,
+            weight_updates: 0,
+            max_cost: default_max_cost(),
+            objective: config.objective,
+            max_size: default_max_size(),
+            admission: match config.admission {
+                true => Some(TinyLfu::new(config.cache_size)),
+                false => None
+            },
+            frequency_aging: config.frequency_aging,
             rng: rand_chacha::ChaCha8Rng::seed_from_u64(10),
-            counter: Default::default()
+            counter: Default::default(),
+            mode: config.mode,
+            learning_rate: config.learning_rate,
+            discount_rate: config.discount_rate,
+            clock: 0,
+            last_miss: None,
+            listener: Listener::default()
+        })
+    }
+
+    /// Returns the weights of the LFU and GDS experts before anything is learned
+    /// The GDS expert only gets a share if it has a policy cache
+    fn initial_weights(gds_cache_size: usize) -> (f64, f64) {
+        match gds_cache_size {
+            0 => (0.5, 0.0),
+            _ => (1.0 / 3.0, 1.0 / 3.0)
+        }
+    }
+
+    /// Returns the current weights of the experts
+    pub fn weights(&self) -> Weights {
+        Weights {
+            lfu: self.lfu_prob,
+            lru: 1.0 - self.lfu_prob - self.gds_prob,
+            gds: self.gds_prob
+        }
+    }
+
+    /// Replaces the weights of the experts, the learner carries on from them unless frozen
+    /// The weights are normalized to sum to exactly 1
+    /// Fails if the weights are not probabilities summing to 1 or give a weight to the GDS expert without a policy cache
+    pub fn set_weights(&mut self, weights: Weights) -> Result<(), LecarError> {
+        weights.validate()?;
+        if weights.gds > 0.0 && self.gds.capacity() == 0 {
+            return Err(LecarError::InvalidConfig("the GDS expert has a weight but no policy cache".to_string()));
         }
+
+        let weights = weights.normalized();
+        self.lfu_prob = weights.lfu;
+        self.gds_prob = weights.gds;
+        Ok(())
+    }
+
+    /// Stops or resumes learning
+    /// Frozen weights stay as they are, history hits still compute their regret
+    pub fn freeze_weights(&mut self, frozen: bool) {
+        self.weights_frozen = frozen;
+    }
+
+    /// Getter for the probability of ejecting through the LFU policy
+    #[cfg(test)]
+    pub(crate) fn lfu_prob(&self) -> f64 {
+        self.lfu_prob
     }
 
     fn get_policy(&mut self) -> Policy {
@@ -109,24 +366,72 @@ impl Controller {
         // if random::<f64>() <= self.lfu_prob {
         if p <= self.lfu_prob {
             Policy::LFU
+        } else if p <= self.lfu_prob + self.gds_prob {
+            Policy::GDS
         } else {
             Policy::LRU
         }
         // Policy::LFU
     }
 
-    fn update_weights(&mut self, time_duration: f64, miss_from: Policy) {
-        let reward = DISCOUNT_RATE.powf(time_duration);
+    /// Rewards the policies that did not eject the item, returning the reward
+    /// The reward is scaled by the cost of the missed item relative to the largest cost inserted
+    /// And by its size relative to the largest size inserted when optimizing the byte hit ratio
+    fn update_weights(&mut self, time_duration: f64, miss_from: Policy, missed_item: &CacheItem) -> f64 {
+        let mut reward = self.discount_rate.powf(time_duration) * missed_item.cost() as f64 / self.max_cost.max(1) as f64;
+        if self.objective == Objective::ByteHitRatio {
+            reward *= missed_item.size() as f64 / self.max_size.max(1) as f64;
+        }
+
+        if self.weights_frozen {
+            return reward;
+        }
+
         let mut new_lfu_prob = self.lfu_prob;
-        let mut new_lru_prob = 1.0 - self.lfu_prob;
+        let mut new_gds_prob = self.gds_prob;
+        let mut new_lru_prob = 1.0 - self.lfu_prob - self.gds_prob;
 
+        let boost = E.powf(self.learning_rate * reward);
         match miss_from {
-            Policy::LFU => new_lru_prob = new_lru_prob * E.powf(LEARNING_RATE * reward),
-            Policy::LRU => new_lfu_prob = new_lfu_prob * E.powf(LEARNING_RATE * reward)
+            Policy::LFU => {
+                new_lru_prob *= boost;
+                new_gds_prob *= boost;
+            },
+            Policy::LRU => {
+                new_lfu_prob *= boost;
+                new_gds_prob *= boost;
+            },
+            Policy::GDS => {
+                new_lfu_prob *= boost;
+                new_lru_prob *= boost;
+            }
         };
 
-        let lfu_prob = new_lfu_prob / (new_lfu_prob + new_lru_prob);
-        self.lfu_prob = (lfu_prob * 100.0).round() / 100.0;
+        let total = new_lfu_prob + new_gds_prob + new_lru_prob;
+        let (lfu_prob, gds_prob) = (new_lfu_prob / total, new_gds_prob / total);
+        match (self.mode, self.full_precision) {
+            (Mode::Classic, false) => {
+                self.lfu_prob = (lfu_prob * 100.0).round() / 100.0;
+                self.gds_prob = (gds_prob * 100.0).round() / 100.0;
+            },
+            _ => {
+                self.lfu_prob = lfu_prob;
+                self.gds_prob = gds_prob;
+            }
+        };
+
+        self.weight_updates = self.weight_updates.saturating_add(1);
+        let mut weights = self.weights();
+        if self.renormalize_every > 0 && self.weight_updates % self.renormalize_every == 0 {
+            weights = weights.normalized();
+        }
+        if self.exploration > 0.0 {
+            weights = weights.floored(self.exploration, self.gds.capacity() > 0);
+        }
+        self.lfu_prob = weights.lfu;
+        self.gds_prob = weights.gds;
+
+        reward
     }
 
     /// Retrieves an item from the cache
@@ -137,16 +442,50 @@ impl Controller {
     /// If an item is ejected from the main cache, it then inserts it into a policy cache
     /// Policy cache ejects an item depending on its policy if it is full in O(1) time
     /// Returns the found item or None
+    /// In paper faithful mode only the main cache is looked into
     pub fn get(&mut self, key: &str) -> Option<u32> {
+        self.get_detailed(key).value()
+    }
+
+    /// Same as `get` but tells where the item was found
+    /// Counts the request in the counter's queries, hits and misses
+    pub fn get_detailed(&mut self, key: &str) -> GetResult {
+        let result = match self.mode {
+            Mode::Classic => self.classic_get(key),
+            Mode::PaperFaithful => self.paper_get(key)
+        };
+        self.count_queries(&[result]);
+
+        result
+    }
+
+    /// Counts lookups in the counter's queries, hits and misses
+    fn count_queries(&mut self, results: &[GetResult]) {
+        let hits = results.iter().filter(|result| result.value().is_some()).count() as u64;
+        self.counter.num_queries = self.counter.num_queries.saturating_add(results.len() as u64);
+        self.counter.hits = self.counter.hits.saturating_add(hits);
+        self.counter.misses = self.counter.misses.saturating_add(results.len() as u64 - hits);
+    }
+
+    /// Retrieves an item from the main cache or the policy caches, counting the request
+    fn classic_get(&mut self, key: &str) -> GetResult {
+
+        self.request(key);
+        self.last_miss = None;
+
         match self.cache.get(key) {
             // HIT
-            Some(item) => Some(item.value()),
+            Some(item) => {
+                self.counter.record_bytes(item.size() as u64, true);
+                GetResult::MainHit(item.value())
+            },
             // MISS
             None => {
                 match self.find_key_in_policy_caches(key) {
                     Some((ejected_item, time_duration, old_policy)) => {
-                        self.update_weights(time_duration, old_policy);
+                        let regret = self.update_weights(time_duration, old_policy, &ejected_item);
                         let value_to_return = ejected_item.value();
+                        self.counter.record_bytes(ejected_item.size() as u64, true);
                         let policy = self.get_policy();
                         let maybe_cache_item = self.cache.insert_with_policy(ejected_item, policy);
 
@@ -155,14 +494,478 @@ impl Controller {
                             policy
                         );
 
-                        Some(value_to_return)
+                        GetResult::HistoryHit { value: value_to_return, policy: old_policy, regret }
                     },
-                    None => None
+                    None => {
+                        self.last_miss = Some(key.to_string());
+                        GetResult::Miss
+                    }
+                }
+            }
+        }
+    }
+
+    /// Counts a request, recording it in the admission filter
+    fn request(&mut self, key: &str) {
+        self.tick();
+        if let Some(admission) = &mut self.admission {
+            admission.record(key);
+        }
+    }
+
+    /// Advances the logical time, aging frequencies at the end of every period
+    fn tick(&mut self) {
+        self.clock = self.clock.saturating_add(1);
+
+        if let Some(period) = self.frequency_aging.period() {
+            if self.clock % period == 0 {
+                self.cache.age_frequencies(self.frequency_aging);
+                self.lfu.age_frequencies(self.frequency_aging);
+                self.lru.age_frequencies(self.frequency_aging);
+                self.gds.age_frequencies(self.frequency_aging);
+            }
+        }
+    }
+
+    /// Asks the admission filter whether a new key may replace the item the policy would eject
+    /// Counts the decision if the main cache is full and the filter had to be consulted
+    fn admits(&mut self, key: &str, policy: Policy) -> bool {
+        if self.cache.contains(key) || !self.cache.full() {
+            return true;
+        }
+
+        let admitted = match (&self.admission, self.cache.victim(policy)) {
+            (Some(admission), Some(victim)) => admission.admit(key, victim.key()),
+            _ => return true
+        };
+
+        match admitted {
+            true => self.counter.admitted = self.counter.admitted.saturating_add(1),
+            false => self.counter.rejected = self.counter.rejected.saturating_add(1)
+        }
+
+        admitted
+    }
+
+    /// Drops the new items of a batch the admission filter estimates to be less popular than the item the policy ejects first
+    /// The first items fill the free slots of the main cache and are admitted as they displace nothing
+    fn admit_batch(&mut self, chunk: Vec<CacheItem>, new_keys: &HashSet<String>, policy: Policy, free: usize) -> Vec<CacheItem> {
+        let victim = match (&self.admission, self.cache.victim(policy)) {
+            (Some(_), Some(victim)) => victim.key().to_string(),
+            _ => return chunk
+        };
+
+        let admission = &self.admission;
+        let (mut admitted, mut rejected) = (0, 0);
+        let chunk = chunk
+            .into_iter()
+            .enumerate()
+            .filter(|(position, item)| {
+                if *position < free || !new_keys.contains(item.key()) {
+                    return true;
+                }
+
+                let admit = match &admission {
+                    Some(admission) => admission.admit(item.key(), &victim),
+                    None => true
+                };
+                match admit {
+                    true => admitted += 1,
+                    false => rejected += 1
+                }
+
+                admit
+            })
+            .map(|(_, item)| item)
+            .collect();
+
+        self.counter.admitted = self.counter.admitted.saturating_add(admitted);
+        self.counter.rejected = self.counter.rejected.saturating_add(rejected);
+
+        chunk
+    }
+
+    /// Looks up an item without touching it, the weights or the learner's RNG
+    /// Policy caches are only looked into, in O(n) time, if asked for
+    /// Returns the item's value and the cache it lives in or None
+    pub fn peek(&self, key: &str, include_histories: bool) -> Option<(u32, Location)> {
+        if let Some(item) = self.cache.peek(key) {
+            return Some((item.value(), Location::Main));
+        }
+
+        if !include_histories {
+            return None;
+        }
+
+        self.lfu
+            .peek(key)
+            .map(|item| (item.get_inner().value(), Location::History(Policy::LFU)))
+            .or_else(|| self.lru.peek(key).map(|item| (item.get_inner().value(), Location::History(Policy::LRU))))
+            .or_else(|| self.gds.peek(key).map(|item| (item.get_inner().value(), Location::History(Policy::GDS))))
+    }
+
+    /// Retrieves a batch of items, as `get_detailed` would for each key
+    /// Items found in a policy cache move back into the main cache together, under a single policy draw
+    /// Counts the batch once in the counter's queries, hits and misses
+    /// In paper faithful mode the keys are looked up one by one
+    pub fn get_many(&mut self, keys: &[&str]) -> Vec<GetResult> {
+        let results: Vec<GetResult> = match self.mode {
+            Mode::PaperFaithful => keys.iter().map(|key| self.paper_get(key)).collect(),
+            Mode::Classic => {
+                let mut pending: IndexMap<String, CacheItem> = IndexMap::new();
+                let mut results = Vec::with_capacity(keys.len());
+                for key in keys {
+                    self.request(key);
+                    if let Some(item) = self.cache.get(key) {
+                        self.counter.record_bytes(item.size() as u64, true);
+                        results.push(GetResult::MainHit(item.value()));
+                        continue;
+                    }
+
+                    if let Some(item) = pending.get_mut(*key) {
+                        item.touch();
+                        self.counter.record_bytes(item.size() as u64, true);
+                        results.push(GetResult::MainHit(item.value()));
+                        continue;
+                    }
+
+                    results.push(match self.find_key_in_policy_caches(key) {
+                        Some((ejected_item, time_duration, old_policy)) => {
+                            let regret = self.update_weights(time_duration, old_policy, &ejected_item);
+                            let value = ejected_item.value();
+                            self.counter.record_bytes(ejected_item.size() as u64, true);
+                            pending.insert(key.to_string(), ejected_item);
+
+                            GetResult::HistoryHit { value, policy: old_policy, regret }
+                        },
+                        None => GetResult::Miss
+                    });
+                }
+                self.insert_batch(pending, &HashSet::new());
+
+                // As after single lookups, only a miss on the last key spares the insert that follows from counting it again
+                self.last_miss = match results.last() {
+                    Some(GetResult::Miss) => keys.last().map(|key| key.to_string()),
+                    _ => None
+                };
+
+                results
+            }
+        };
+
+        self.count_queries(&results);
+
+        results
+    }
+
+    /// Inserts a batch of items, as `insert` would for each of them
+    /// Room is made in the main cache for all the new items at once, under a single policy draw
+    /// Returns where each key was found with its previous value
+    /// In paper faithful mode the items are inserted one by one
+    pub fn insert_many(&mut self, items: &[(&str, u32)]) -> Vec<GetResult> {
+        if self.mode == Mode::PaperFaithful {
+            return items
+                .iter()
+                .map(|(key, value)| {
+                    let previous = self.peek(key, false).map_or(GetResult::Miss, |(previous, _)| GetResult::MainHit(previous));
+                    self.paper_insert(key, *value, InsertOptions::default());
+                    previous
+                })
+                .collect();
+        }
+
+        let mut pending: IndexMap<String, CacheItem> = IndexMap::new();
+        let mut new_keys = HashSet::new();
+        let mut results = Vec::with_capacity(items.len());
+        for (key, value) in items {
+            // The request was already counted by the miss preceding this insert, whose bytes are now known
+            match self.last_miss.take().as_deref() == Some(*key) {
+                true => self.counter.record_bytes(1, false),
+                false => self.request(key)
+            }
+            if let Some(previous) = self.cache.update(key, *value) {
+                results.push(GetResult::MainHit(previous));
+                continue;
+            }
+
+            if let Some(item) = pending.get_mut(*key) {
+                results.push(GetResult::MainHit(item.value()));
+                item.update(*value);
+                continue;
+            }
+
+            results.push(match self.find_key_in_policy_caches(key) {
+                Some((mut ejected_item, time_duration, old_policy)) => {
+                    let regret = self.update_weights(time_duration, old_policy, &ejected_item);
+                    let previous = ejected_item.value();
+                    ejected_item.update(*value);
+                    pending.insert(key.to_string(), ejected_item);
+
+                    GetResult::HistoryHit { value: previous, policy: old_policy, regret }
+                },
+                None => {
+                    pending.insert(key.to_string(), CacheItem::new(key.to_string(), *value));
+                    new_keys.insert(key.to_string());
+                    GetResult::Miss
+                }
+            });
+        }
+        self.insert_batch(pending, &new_keys);
+
+        results
+    }
+
+    /// Pins an item of the main cache so that it is never ejected, it still counts against the capacity
+    /// Fails if the item is not in the main cache or if pinning it would leave no item to eject
+    pub fn pin(&mut self, key: &str) -> Result<(), LecarError> {
+        let pinned_len = self.cache.pinned_len();
+        let capacity = self.cache.capacity();
+
+        match self.cache.peek_mut(key) {
+            None => Err(LecarError::KeyNotFound(key.to_string())),
+            Some(item) if item.pinned() => Ok(()),
+            Some(_) if pinned_len + 1 >= capacity => Err(LecarError::FullyPinned(key.to_string())),
+            Some(item) => {
+                item.set_pinned(true);
+                Ok(())
+            }
+        }
+    }
+
+    /// Unpins an item of the main cache
+    /// Returns whether the item was pinned
+    pub fn unpin(&mut self, key: &str) -> bool {
+        match self.cache.peek_mut(key) {
+            Some(item) if item.pinned() => {
+                item.set_pinned(false);
+                true
+            },
+            _ => false
+        }
+    }
+
+    /// Returns the number of pinned items
+    pub fn pinned_len(&self) -> usize {
+        self.cache.pinned_len()
+    }
+
+    /// Resizes the main cache and the LFU and LRU policy caches in place
+    /// Growing keeps every item, shrinking the main cache ejects items one by one under policies drawn by the learner
+    /// The ejected items go into the policy caches, which are resized first
+    /// Each item is found in a single pass over the main cache
+    /// The GDS policy cache keeps its size, as enabling or disabling GDS would change the policies the learner draws
+    /// Fails if the main cache would have no capacity or only room for its pinned items
+    pub fn resize(&mut self, cache_size: usize, lfu_cache_size: usize, lru_cache_size: usize) -> Result<(), LecarError> {
+        if cache_size == 0 {
+            return Err(LecarError::InvalidConfig("the main cache needs a capacity of at least 1".to_string()));
+        }
+
+        let pinned_len = self.cache.pinned_len();
+        if pinned_len >= cache_size {
+            return Err(LecarError::InvalidConfig(format!("a main cache of {} items would be fully pinned by {} pinned items", cache_size, pinned_len)));
+        }
+
+        match self.mode {
+            Mode::Classic => {
+                self.lfu.resize(lfu_cache_size);
+                self.lru.resize(lru_cache_size);
+            },
+            Mode::PaperFaithful => {
+                self.lfu.resize_fifo(lfu_cache_size);
+                self.lru.resize_fifo(lru_cache_size);
+            }
+        }
+
+        self.cache.set_capacity(cache_size);
+        while self.cache.len() > cache_size {
+            let policy = self.get_policy();
+            let ejected_item = self.cache.eject(policy);
+            self.insert_into_policy_cache(ejected_item, policy);
+        }
+
+        Ok(())
+    }
+
+    /// Removes an item from whichever cache it lives in
+    /// Returns its value and the cache it was removed from or None
+    pub fn remove(&mut self, key: &str) -> Option<(u32, Location)> {
+        let removed = match self.cache.remove(key) {
+            Some(item) => Some((item.value(), Location::Main)),
+            None => self.lfu
+                .maybe_eject_key(key)
+                .map(|item| (item.get_inner().value(), Location::History(Policy::LFU)))
+                .or_else(|| self.lru.maybe_eject_key(key).map(|item| (item.get_inner().value(), Location::History(Policy::LRU))))
+                .or_else(|| self.gds.maybe_eject_key(key).map(|item| (item.get_inner().value(), Location::History(Policy::GDS))))
+        };
+
+        if let Some((value, location)) = removed {
+            self.listener.notify(key, value, location, Removal::Removed);
+        }
+
+        removed
+    }
+
+    /// Sets the callback told of every item the main cache ejects into a policy cache
+    /// And of every item that `clear`, `drain` or `remove` takes out of the controller
+    /// Items the policy caches forget when full are not reported
+    /// The listener is not part of snapshots and has to be set again on a restored controller
+    pub fn set_eviction_listener(&mut self, listener: Option<EvictionListener>) {
+        self.listener.set(listener);
+    }
+
+    /// Empties the main cache and the policy caches, keeping the learned weights and the admission filter's sketch
+    /// The GreedyDual inflation starts over, as no item is left to be compared against it
+    /// Pinned items are dropped too
+    pub fn clear(&mut self) {
+        let items = self.take_all();
+        self.counter.cleared = self.counter.cleared.saturating_add(items.len() as u64);
+        for (key, value, location) in &items {
+            self.listener.notify(key, *value, *location, Removal::Cleared);
+        }
+    }
+
+    /// Empties the main cache and the policy caches as `clear` does
+    /// Yields every item with the cache it lived in
+    /// The main cache comes first in insertion order, then each policy cache in eviction order
+    pub fn drain(&mut self) -> impl Iterator<Item = (String, u32, Location)> {
+        let items = self.take_all();
+        self.counter.drained = self.counter.drained.saturating_add(items.len() as u64);
+        for (key, value, location) in &items {
+            self.listener.notify(key, *value, *location, Removal::Drained);
+        }
+
+        items.into_iter()
+    }
+
+    /// Resets the weights of the experts to what they were before learning anything, keeping every item
+    pub fn reset_learner(&mut self) {
+        let (lfu_prob, gds_prob) = Self::initial_weights(self.gds.capacity());
+        self.lfu_prob = lfu_prob;
+        self.gds_prob = gds_prob;
+        self.counter.learner_resets = self.counter.learner_resets.saturating_add(1);
+    }
+
+    /// Removes every item from the main cache and the policy caches
+    fn take_all(&mut self) -> Vec<(String, u32, Location)> {
+        let mut items: Vec<(String, u32, Location)> = self.cache
+            .drain()
+            .map(|item| (item.key().to_string(), item.value(), Location::Main))
+            .collect();
+
+        items.extend(Self::drain_history(self.mode, &mut self.lfu, Policy::LFU));
+        items.extend(Self::drain_history(self.mode, &mut self.lru, Policy::LRU));
+        items.extend(Self::drain_history(self.mode, &mut self.gds, Policy::GDS));
+
+        items
+    }
+
+    /// Removes every item from a policy cache, in eviction order
+    fn drain_history<I: ICacheItemWrapper>(mode: Mode, cache: &mut Cache<BinaryHeap<I>>, policy: Policy) -> impl Iterator<Item = (String, u32, Location)> {
+        let mut items = cache.drain();
+        if mode == Mode::PaperFaithful {
+            items.sort_by_key(|item| item.evicted_at());
+        }
+
+        items.into_iter().map(move |item| {
+            let (item, _, _) = item.into_inner();
+            (item.key().to_string(), item.value(), Location::History(policy))
+        })
+    }
+
+    /// Removes a batch of items, as `remove` would for each key
+    pub fn remove_many(&mut self, keys: &[&str]) -> Vec<Option<(u32, Location)>> {
+        keys.iter().map(|key| self.remove(key)).collect()
+    }
+
+    /// Inserts items missing from the main cache
+    /// Ejects as many items as needed in a single pass per policy draw, the main cache taking at most its unpinned capacity per draw
+    /// The given new keys go through the admission filter
+    fn insert_batch(&mut self, items: IndexMap<String, CacheItem>, new_keys: &HashSet<String>) {
+        let capacity = self.cache.capacity().saturating_sub(self.cache.pinned_len()).max(1);
+        let mut items = items.into_iter().map(|(_, item)| item).peekable();
+
+        while items.peek().is_some() {
+            let mut chunk: Vec<CacheItem> = items.by_ref().take(capacity).collect();
+            let mut overflow = (self.cache.len() + chunk.len()).saturating_sub(self.cache.capacity());
+            if overflow > 0 {
+                let policy = self.get_policy();
+                let free = self.cache.capacity().saturating_sub(self.cache.len());
+                chunk = self.admit_batch(chunk, new_keys, policy, free);
+                overflow = (self.cache.len() + chunk.len()).saturating_sub(self.cache.capacity());
+                for ejected_item in self.cache.eject_many(overflow, policy) {
+                    self.insert_into_policy_cache(Some(ejected_item), policy);
                 }
             }
+
+            // Pinning always leaves an unpinned slot, so enough items were ejected to fit the chunk
+            for item in chunk {
+                let rejected = self.cache.insert(item);
+                debug_assert!(rejected.is_none(), "the main cache had no room left for a batched item");
+            }
         }
     }
 
+    /// Gets the entry of the given key for in place manipulation
+    /// Counts as a single request, a key found in a policy cache updates the learner once
+    /// In classic mode such a key moves back into the main cache and is occupied, in paper faithful mode it is vacant
+    pub fn entry(&mut self, key: &str) -> Entry<'_> {
+        self.get_detailed(key);
+
+        match self.cache.get_index_of(key) {
+            Some(index) => Entry::Occupied(OccupiedEntry::new(self.cache.slot_mut(index))),
+            None => Entry::Vacant(VacantEntry::new(self, key.to_string()))
+        }
+    }
+
+    /// Iterates over the keys and values of the main cache in insertion order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, u32)> {
+        self.cache.iter().map(|item| (item.key(), item.value()))
+    }
+
+    /// Iterates over the keys of the main cache in insertion order
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.cache.keys()
+    }
+
+    /// Iterates over the values of the main cache in insertion order
+    pub fn values(&self) -> impl Iterator<Item = u32> + '_ {
+        self.cache.iter().map(|item| item.value())
+    }
+
+    /// Returns the items of the LFU policy cache, the next one to be ejected first
+    pub fn lfu_history(&self) -> impl Iterator<Item = HistoryEntry<'_>> {
+        self.history(self.lfu.items_in_eviction_order())
+    }
+
+    /// Returns the items of the LRU policy cache, the next one to be ejected first
+    pub fn lru_history(&self) -> impl Iterator<Item = HistoryEntry<'_>> {
+        self.history(self.lru.items_in_eviction_order())
+    }
+
+    /// Returns the items of the GDS policy cache, the next one to be ejected first
+    pub fn gds_history(&self) -> impl Iterator<Item = HistoryEntry<'_>> {
+        self.history(self.gds.items_in_eviction_order())
+    }
+
+    /// Describes policy cache items given in eviction order
+    /// Paper faithful policy caches eject the item that entered first instead
+    fn history<'a, I: ICacheItemWrapper>(&self, mut items: Vec<&'a I>) -> impl Iterator<Item = HistoryEntry<'a>> {
+        if self.mode == Mode::PaperFaithful {
+            items.sort_by_key(|item| item.evicted_at());
+        }
+
+        let (mode, clock) = (self.mode, self.clock);
+        items.into_iter().map(move |item| HistoryEntry {
+            key: item.get_inner_key(),
+            value: item.get_inner().value(),
+            frequency: item.get_inner().frequency(),
+            age: match mode {
+                Mode::Classic => item.get_duration(),
+                Mode::PaperFaithful => clock.saturating_sub(item.evicted_at()) as f64
+            }
+        })
+    }
+
     pub fn get_index(&self, index: usize) -> Option<&str> {
         self.cache.get_index(index)
     }
@@ -180,15 +983,34 @@ impl Controller {
     /// Otherwise it inserts the item and ejects another item via a given policy from the learner
     /// It then inserts that ejected item into a policy cache which will eject an item if full
     pub fn insert(&mut self, key: &str, value: u32) {
+        self.insert_with_options(key, value, InsertOptions::default())
+    }
+
+    /// Same as `insert` but with the given options
+    pub fn insert_with_options(&mut self, key: &str, value: u32, options: InsertOptions) {
+        self.max_cost = self.max_cost.max(options.cost.unwrap_or(1));
+        self.max_size = self.max_size.max(options.size.unwrap_or(1));
+
+        if self.mode == Mode::PaperFaithful {
+            return self.paper_insert(key, value, options);
+        }
+
+        // The request was already counted by the miss preceding this insert, whose bytes are now known
+        match self.last_miss.take().as_deref() == Some(key) {
+            true => self.counter.record_bytes(options.size.unwrap_or(1) as u64, false),
+            false => self.request(key)
+        }
+
         // Ejected cache item from either the LFU or the LRU, if it exists in either
         match self.find_key_in_policy_caches(key) {
             // If cache item existed in policy caches
             // Update it
             // Insert into main cache given the new policy
             Some((mut ejected_item, time_duration, old_policy)) => {
+                self.update_weights(time_duration, old_policy, &ejected_item);
                 ejected_item.update(value);
+                options.apply(&mut ejected_item);
 
-                self.update_weights(time_duration, old_policy);
                 let policy = self.get_policy();
 
                 let maybe_cache_item = self.cache.insert_with_policy(ejected_item, policy);
@@ -199,10 +1021,14 @@ impl Controller {
                 )
             },
             // Cache item was not found in the policy caches
-            // Add it to cache
+            // Add it to cache, unless the admission filter rejects it
             None => {
                 let policy = self.get_policy();
-                let maybe_cache_item = self.cache.insert_with_policy(CacheItem::new(key.to_string(), value), policy);
+                if !self.admits(key, policy) {
+                    return;
+                }
+
+                let maybe_cache_item = self.cache.insert_with_policy(self.new_item(key, value, options), policy);
 
                 self.insert_into_policy_cache(
                     maybe_cache_item,
@@ -212,39 +1038,289 @@ impl Controller {
         }
     }
 
-    /// Given a cache item and a policy, insert into the given policy cache
-    fn insert_into_policy_cache(&mut self, maybe_cache_item: Option<CacheItem>, policy: Policy) {
-        if let Some(cache_item) = maybe_cache_item {
-            match policy {
-                Policy::LFU => self.lfu.insert(LFUCacheItem::new(cache_item)),
-                Policy::LRU => self.lru.insert(LRUCacheItem::new(cache_item))
+    /// Inserts the value of a vacant entry as `insert` would, bypassing the admission filter
+    /// The main cache makes room for it by ejecting a single item, unless every item is pinned
+    /// Returns the slot of the value in the main cache
+    pub(super) fn insert_vacant(&mut self, key: &str, value: u32) -> &mut u32 {
+        // The request was already counted by the miss preceding this insert, whose bytes are now known
+        match (self.last_miss.take().as_deref() == Some(key), self.mode) {
+            (true, _) => self.counter.record_bytes(1, false),
+            (false, Mode::Classic) => self.request(key),
+            (false, Mode::PaperFaithful) => self.tick()
+        }
+
+        let item = match self.find_key_in_policy_caches(key) {
+            Some((mut ejected_item, time_duration, old_policy)) => {
+                self.update_weights(time_duration, old_policy, &ejected_item);
+                ejected_item.update(value);
+                ejected_item
+            },
+            None => InsertOptions::default().item(key, value)
+        };
+
+        // Classic mode draws a policy on every insert, the paper only when an item has to be ejected
+        let full = self.cache.full() && !self.cache.contains(key);
+        if self.mode == Mode::Classic || full {
+            let policy = self.get_policy();
+            if full {
+                let ejected_item = self.cache.eject(policy);
+                self.insert_into_policy_cache(ejected_item, policy);
             }
         }
+
+        self.cache.insert_slot(item).value_mut()
+    }
+
+    /// Retrieves an item from the main cache, counting the request
+    fn paper_get(&mut self, key: &str) -> GetResult {
+        self.tick();
+
+        match self.cache.get(key) {
+            Some(item) => {
+                self.last_miss = None;
+                self.counter.record_bytes(item.size() as u64, true);
+                GetResult::MainHit(item.value())
+            },
+            None => {
+                self.last_miss = Some(key.to_string());
+                GetResult::Miss
+            }
+        }
+    }
+
+    /// Inserts an item following Algorithm 1 of the paper
+    /// If the item is found in a policy cache the weights are updated and the item keeps its frequency
+    /// The learner picks a policy only if the main cache has to eject an item
+    /// Which then goes into that policy's cache, ejecting the oldest item there if full
+    fn paper_insert(&mut self, key: &str, value: u32, options: InsertOptions) {
+        // The request was already counted by the miss preceding this insert, whose bytes are now known
+        match self.last_miss.take().as_deref() == Some(key) {
+            true => self.counter.record_bytes(options.size.unwrap_or(1) as u64, false),
+            false => self.tick()
+        }
+
+        if self.cache.contains(key) {
+            self.cache.insert_with_paper_policy(self.new_item(key, value, options), Policy::LRU);
+            return;
+        }
+
+        let cache_item = match self.find_key_in_policy_caches(key) {
+            Some((mut ejected_item, time_duration, old_policy)) => {
+                self.update_weights(time_duration, old_policy, &ejected_item);
+                ejected_item.update(value);
+                options.apply(&mut ejected_item);
+                ejected_item
+            },
+            None => options.item(key, value)
+        };
+
+        if !self.cache.full() {
+            self.cache.insert_with_paper_policy(cache_item, Policy::LRU);
+            return;
+        }
+
+        let policy = self.get_policy();
+        let maybe_cache_item = self.cache.insert_with_paper_policy(cache_item, policy);
+        self.insert_into_policy_cache(maybe_cache_item, policy);
+    }
+
+    /// Instantiates the item to insert with the options
+    /// Options left unset keep the cost and size of the item already in the main cache
+    fn new_item(&self, key: &str, value: u32, options: InsertOptions) -> CacheItem {
+        let mut item = options.item(key, value);
+        if let Some(existing) = self.cache.peek(key) {
+            item.set_cost(options.cost.unwrap_or_else(|| existing.cost()));
+            item.set_size(options.size.unwrap_or_else(|| existing.size()));
+        }
+
+        item
+    }
+
+    /// Given a cache item ejected from the main cache and a policy, insert into the given policy cache
+    /// In paper faithful mode a full policy cache ejects the item that entered it first
+    fn insert_into_policy_cache(&mut self, maybe_cache_item: Option<CacheItem>, policy: Policy) {
+        let cache_item = match maybe_cache_item {
+            Some(cache_item) => cache_item,
+            None => return
+        };
+        self.listener.notify(cache_item.key(), cache_item.value(), Location::Main, Removal::Evicted);
+
+        match (self.mode, policy) {
+            (Mode::Classic, Policy::LFU) => self.lfu.insert(LFUCacheItem::new(cache_item, self.clock)),
+            (Mode::Classic, Policy::LRU) => self.lru.insert(LRUCacheItem::new(cache_item, self.clock)),
+            (Mode::Classic, Policy::GDS) => self.gds.insert(GDSCacheItem::new(cache_item, self.clock)),
+            (Mode::PaperFaithful, Policy::LFU) => self.lfu.insert_fifo(LFUCacheItem::new(cache_item, self.clock)),
+            (Mode::PaperFaithful, Policy::LRU) => self.lru.insert_fifo(LRUCacheItem::new(cache_item, self.clock)),
+            (Mode::PaperFaithful, Policy::GDS) => self.gds.insert_fifo(GDSCacheItem::new(cache_item, self.clock))
+        }
     }
 
     /// Given a key, find an item in a policy cache if it exists on one
+    /// Returns the item with the time it spent in the policy cache, in seconds or in requests in paper faithful mode
     fn find_key_in_policy_caches(&mut self, key: &str) -> Option<(CacheItem, f64, Policy)> {
-        self.lfu
+        let ejected = self.lfu
             .maybe_eject_key(key)
-            .and_then(|cache_item| Some(cache_item.into_inner()))
+            .map(|cache_item| (cache_item.evicted_at(), cache_item.into_inner()))
             .or_else(|| self.lru
                 .maybe_eject_key(key)
-                .and_then(|cache_item| Some(cache_item.into_inner()))
+                .map(|cache_item| (cache_item.evicted_at(), cache_item.into_inner()))
             )
+            .or_else(|| self.gds
+                .maybe_eject_key(key)
+                .map(|cache_item| (cache_item.evicted_at(), cache_item.into_inner()))
+            );
+
+        ejected.map(|(evicted_at, (cache_item, time_duration, policy))| {
+            match self.mode {
+                Mode::Classic => (cache_item, time_duration, policy),
+                Mode::PaperFaithful => (cache_item, self.clock.saturating_sub(evicted_at) as f64, policy)
+            }
+        })
     }
 
-    /// Returns a tuple of the current sizes of each cache
+    /// Returns a tuple of the current sizes of the main, LFU and LRU caches
     pub fn len(&self) -> (usize, usize, usize) {
         (self.cache.len(), self.lfu.len(), self.lru.len())
     }
 
+    /// Returns the current size of the GDS policy cache
+    pub fn gds_len(&self) -> usize {
+        self.gds.len()
+    }
+
     pub fn full(&self) -> bool {
-        self.cache.len() == self.cache.capacity
+        self.cache.len() == self.cache.capacity()
+    }
+
+    pub fn print_size(&self) -> Result<usize, LecarError> {
+        let serialized = serde_json::to_string(&self)?;
+
+        Ok(serialized.len())
+    }
+
+    /// Serializes the controller, including its learned weights and RNG state
+    pub fn to_snapshot(&self) -> Result<Vec<u8>, LecarError> {
+        Ok(serde_json::to_vec(self)?)
+    }
+
+    /// Restores a controller from a snapshot
+    /// The snapshot is untrusted, anything that does not decode or breaks an invariant is rejected
+    pub fn from_snapshot(bytes: &[u8]) -> Result<Self, LecarError> {
+        let controller: Self = serde_json::from_slice(bytes)?;
+        controller.check_invariants().map_err(LecarError::InvalidSnapshot)?;
+
+        Ok(controller)
+    }
+
+    /// Lists the policy caches whose size is unusual compared to the main cache, as `ControllerConfig::warnings` does
+    /// Covers controllers that were resized or restored from a snapshot too
+    pub fn warnings(&self) -> Vec<String> {
+        self.config().warnings()
     }
 
-    pub fn print_size(&self) -> usize {
-        let serialized = serde_json::to_string(&self).unwrap();
+    /// Returns the configuration the controller currently runs with
+    fn config(&self) -> ControllerConfig {
+        ControllerConfig {
+            cache_size: self.cache.capacity(),
+            lfu_cache_size: self.lfu.capacity(),
+            lru_cache_size: self.lru.capacity(),
+            gds_cache_size: self.gds.capacity(),
+            mode: self.mode,
+            objective: self.objective,
+            admission: self.admission.is_some(),
+            frequency_aging: self.frequency_aging,
+            auto_size: false,
+            weights: None,
+            exploration: self.exploration,
+            full_precision: self.full_precision,
+            renormalize_every: self.renormalize_every,
+            learning_rate: self.learning_rate,
+            discount_rate: self.discount_rate
+        }
+    }
+
+    /// Verifies the internal consistency of the controller
+    /// The main cache must have a capacity that is not fully pinned and every cache must be within its capacity with a valid ordering
+    /// No key may live in more than one cache, and the LFU and GDS probabilities must be within [0, 1] without summing over 1
+    /// The settings must pass the same checks as a configuration given to `with_config`
+    pub fn check_invariants(&self) -> Result<(), String> {
+        if self.cache.capacity() == 0 {
+            return Err("main cache has no capacity".to_string());
+        }
+
+        if self.cache.pinned_len() >= self.cache.capacity() {
+            return Err("main cache is fully pinned".to_string());
+        }
+
+        self.cache.check_invariants()?;
+        self.lfu.check_invariants()?;
+        self.lru.check_invariants()?;
+        self.gds.check_invariants()?;
+        if let Some(admission) = &self.admission {
+            admission.check_invariants()?;
+        }
+        self.config().validate().map_err(|err| err.to_string())?;
+
+        if !(0.0..=1.0).contains(&self.lfu_prob) {
+            return Err(format!("LFU probability {} is outside of [0, 1]", self.lfu_prob));
+        }
+
+        if !(0.0..=1.0).contains(&self.gds_prob) || self.lfu_prob + self.gds_prob > 1.0 + WEIGHT_TOLERANCE {
+            return Err(format!("GDS probability {} is outside of [0, {}]", self.gds_prob, 1.0 - self.lfu_prob));
+        }
+
+        let mut seen = HashSet::new();
+        let mut keys = self.cache.keys().chain(self.lfu.keys()).chain(self.lru.keys()).chain(self.gds.keys());
+        match keys.find(|key| !seen.insert(*key)) {
+            Some(key) => Err(format!("key `{}` lives in more than one cache", key)),
+            None => Ok(())
+        }
+    }
+}
+
+/// Implementation of ICache for the Controller
+/// Both policy caches are sized to the main cache as in the paper
+/// As `ICache::new` cannot fail, a capacity of zero is raised to a single item; the simulator rejects it instead
+impl ICache for Controller {
+    fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+
+        Controller::new(capacity, capacity, capacity).unwrap_or_else(|err| unreachable!("{}", err))
+    }
+
+    fn contains(&self, key: &str) -> bool {
+        self.cache.contains(key)
+    }
+
+    fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    fn full(&self) -> bool {
+        Controller::full(self)
+    }
+}
+
+/// Implementation of IReplacementCache for the Controller
+/// Only the main cache can hit, keys found in a policy cache are misses as in the paper
+/// The insert following such a miss updates the weights and moves the key back into the main cache
+impl IReplacementCache for Controller {
+    fn name(&self) -> &str {
+        "LeCaR"
+    }
+
+    fn get(&mut self, key: &str) -> Option<u32> {
+        match self.mode {
+            Mode::Classic => self.cache.get(key).map(|item| item.value()),
+            Mode::PaperFaithful => Controller::get(self, key)
+        }
+    }
+
+    fn insert(&mut self, key: &str, value: u32) {
+        Controller::insert(self, key, value)
+    }
 
-        serialized.len()
+    fn insert_sized(&mut self, key: &str, value: u32, size: u32) {
+        self.insert_with_options(key, value, InsertOptions { size: Some(size), ..Default::default() })
     }
 }
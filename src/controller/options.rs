@@ -0,0 +1,33 @@
+use crate::cache::CacheItem;
+
+/// Options given with an insert
+/// Options left unset keep the value of an item already cached, or default to 1 for a new item
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub struct InsertOptions {
+    /// Cost of missing the item, in any unit as long as it is the same for every insert
+    /// The GDS policy keeps costly items longer and the reward of a history hit grows with the cost of the missed item
+    pub cost: Option<u32>,
+    /// Size of the object, in bytes
+    /// When optimizing the byte hit ratio, the reward of a history hit grows with the size of the missed item
+    pub size: Option<u32>
+}
+
+impl InsertOptions {
+    /// Instantiates a cache item with the options
+    pub(super) fn item(&self, key: &str, value: u32) -> CacheItem {
+        let mut item = CacheItem::with_cost(key.to_string(), value, self.cost.unwrap_or(1));
+        item.set_size(self.size.unwrap_or(1));
+
+        item
+    }
+
+    /// Sets the options that were given on an existing item
+    pub(super) fn apply(&self, item: &mut CacheItem) {
+        if let Some(cost) = self.cost {
+            item.set_cost(cost);
+        }
+        if let Some(size) = self.size {
+            item.set_size(size);
+        }
+    }
+}
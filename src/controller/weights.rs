@@ -0,0 +1,73 @@
+use super::constants::WEIGHT_TOLERANCE;
+use crate::error::LecarError;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+/// Probabilities of ejecting through each policy, adjusted by the learner after every mistake
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Debug)]
+pub struct Weights {
+    pub lfu: f64,
+    pub lru: f64,
+    /// Zero unless the cost aware GDS expert is enabled
+    #[serde(default)]
+    pub gds: f64
+}
+
+impl Weights {
+    /// Verifies every weight is within [0, 1] and that the weights sum to 1, give or take `WEIGHT_TOLERANCE`
+    pub fn validate(&self) -> Result<(), LecarError> {
+        if [self.lfu, self.lru, self.gds].iter().any(|weight| !(0.0..=1.0).contains(weight)) {
+            return Err(LecarError::InvalidConfig(format!("weights {:?} are not all within [0, 1]", self)));
+        }
+
+        if (self.lfu + self.lru + self.gds - 1.0).abs() > WEIGHT_TOLERANCE {
+            return Err(LecarError::InvalidConfig(format!("weights {:?} do not sum to 1", self)));
+        }
+
+        Ok(())
+    }
+
+    /// Rescales the weights so that they sum to 1, negative weights left by rounding errors count as 0
+    pub fn normalized(&self) -> Self {
+        let [lfu, lru, gds] = [self.lfu, self.lru, self.gds].map(|weight| weight.max(0.0));
+        let total = lfu + lru + gds;
+        if total <= 0.0 {
+            return *self;
+        }
+
+        Self { lfu: lfu / total, lru: lru / total, gds: gds / total }
+    }
+
+    /// Raises every weight under the floor up to it, taking the difference from the weights above it in proportion
+    /// The GDS weight is left out when the expert is disabled
+    /// The floor must leave room for the other weights, at most 1 / the number of experts
+    pub fn floored(&self, floor: f64, gds_enabled: bool) -> Self {
+        let mut weights = [self.lfu, self.lru, self.gds];
+        let experts = if gds_enabled { 3 } else { 2 };
+        let active = &mut weights[..experts];
+
+        let deficit: f64 = active.iter().map(|weight| (floor - weight).max(0.0)).sum();
+        let surplus: f64 = active.iter().map(|weight| (weight - floor).max(0.0)).sum();
+        if deficit <= 0.0 || surplus <= 0.0 {
+            return *self;
+        }
+
+        let scale = ((surplus - deficit) / surplus).max(0.0);
+        active.iter_mut().for_each(|weight| *weight = floor + (*weight - floor).max(0.0) * scale);
+
+        Self { lfu: weights[0], lru: weights[1], gds: weights[2] }
+    }
+
+    /// Reads weights written by `save`, such as those learned by a previous run
+    pub fn load(path: &str) -> Result<Self, LecarError> {
+        let weights: Self = serde_json::from_slice(&fs::read(path)?)?;
+        weights.validate()?;
+
+        Ok(weights)
+    }
+
+    /// Writes the weights to the given file as JSON
+    pub fn save(&self, path: &str) -> Result<(), LecarError> {
+        Ok(fs::write(path, serde_json::to_vec(self)?)?)
+    }
+}
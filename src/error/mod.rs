@@ -0,0 +1,61 @@
+use std::fmt;
+use std::io;
+
+/// Errors raised by the fallible operations of the crate
+#[derive(Debug)]
+pub enum LecarError {
+    /// The controller configuration cannot describe a working cache
+    InvalidConfig(String),
+    /// Reading or writing a file failed
+    Io(io::Error),
+    /// Serializing or deserializing the controller failed
+    Encoding(serde_json::Error),
+    /// A trace record could not be parsed
+    Parse { line: usize, message: String },
+    /// A snapshot decoded but describes an inconsistent controller
+    InvalidSnapshot(String),
+    /// The key does not live in the main cache
+    KeyNotFound(String),
+    /// Pinning the key would leave no item of the main cache to eject
+    FullyPinned(String)
+}
+
+impl fmt::Display for LecarError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidConfig(message) => write!(f, "invalid configuration: {}", message),
+            Self::Io(err) => write!(f, "io error: {}", err),
+            Self::Encoding(err) => write!(f, "unable to encode or decode controller: {}", err),
+            Self::Parse { line, message } => write!(f, "invalid trace record on line {}: {}", line, message),
+            Self::InvalidSnapshot(violation) => write!(f, "invalid snapshot: {}", violation),
+            Self::KeyNotFound(key) => write!(f, "key `{}` is not in the main cache", key),
+            Self::FullyPinned(key) => write!(f, "pinning key `{}` would leave the main cache fully pinned", key)
+        }
+    }
+}
+
+impl std::error::Error for LecarError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Encoding(err) => Some(err),
+            Self::InvalidConfig(_)
+            | Self::Parse { .. }
+            | Self::InvalidSnapshot(_)
+            | Self::KeyNotFound(_)
+            | Self::FullyPinned(_) => None
+        }
+    }
+}
+
+impl From<io::Error> for LecarError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for LecarError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Encoding(err)
+    }
+}
@@ -1,8 +1,15 @@
-#![feature(binary_heap_retain)]
-#![feature(associated_type_defaults)]
 
+pub mod admission;
+pub mod baselines;
 pub mod cache;
 pub mod controller;
+pub mod error;
+pub mod simulator;
+pub mod sketch;
+pub mod trace;
+pub mod workload;
+
+pub use error::LecarError;
 
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,215 @@
+use crate::baselines::{Arc, Belady, Lfu, Lirs, Lru, TwoQ, WTinyLfu};
+use crate::cache::{ICache, IReplacementCache};
+use crate::controller::Controller;
+use crate::error::LecarError;
+use crate::trace::{Access, Op};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Outcome of replaying accesses against a cache
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct SimulationResult {
+    pub name: String,
+    pub requests: u64,
+    pub hits: u64,
+    pub writes: u64,
+    /// Bytes requested by reads
+    #[serde(default)]
+    pub bytes: u64,
+    /// Bytes served by reads that hit
+    #[serde(default)]
+    pub hit_bytes: u64
+}
+
+impl SimulationResult {
+    /// Number of reads that missed
+    pub fn misses(&self) -> u64 {
+        self.requests - self.hits
+    }
+
+    /// Fraction of reads that hit, 0 if there were no reads
+    pub fn hit_ratio(&self) -> f64 {
+        match self.requests {
+            0 => 0.0,
+            requests => self.hits as f64 / requests as f64
+        }
+    }
+
+    /// Fraction of the bytes requested by reads that hit, 0 if no bytes were requested
+    pub fn byte_hit_ratio(&self) -> f64 {
+        match self.bytes {
+            0 => 0.0,
+            bytes => self.hit_bytes as f64 / bytes as f64
+        }
+    }
+}
+
+/// Replacement decision taken by a cache for a single request
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+pub enum Decision {
+    /// The key was already cached
+    Hit,
+    /// The key was cached without evicting anything
+    Insert,
+    /// The key was cached in place of the given key
+    Replace(String),
+    /// The key was not cached
+    Bypass
+}
+
+/// Decisions taken by a cache over a trace
+/// Holds one decision per read and write, deletes are skipped
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct DecisionReport {
+    pub name: String,
+    pub capacity: usize,
+    pub requests: u64,
+    pub hits: u64,
+    pub decisions: Vec<Decision>
+}
+
+impl DecisionReport {
+    /// Fraction of reads that hit, 0 if there were no reads
+    pub fn hit_ratio(&self) -> f64 {
+        match self.requests {
+            0 => 0.0,
+            requests => self.hits as f64 / requests as f64
+        }
+    }
+
+    /// Fraction of requests on which both reports took the same decision
+    pub fn agreement(&self, other: &DecisionReport) -> f64 {
+        let total = self.decisions.len().max(other.decisions.len());
+        if total == 0 {
+            return 1.0;
+        }
+
+        let agreed = self.decisions
+            .iter()
+            .zip(&other.decisions)
+            .filter(|(ours, theirs)| ours == theirs)
+            .count();
+
+        agreed as f64 / total as f64
+    }
+}
+
+/// Replays the accesses against the cache like `simulate`, recording the decision taken for each request
+/// Evicted keys are found by checking which previously cached key is gone, which is O(n) per eviction
+pub fn record<C: IReplacementCache>(cache: &mut C, capacity: usize, accesses: &[Access]) -> DecisionReport {
+    let keys: Vec<&str> = accesses
+        .iter()
+        .filter(|access| access.op != Op::Delete)
+        .map(|access| access.key.as_str())
+        .collect();
+    cache.prepare(&keys);
+
+    let mut report = DecisionReport { name: cache.name().to_string(), capacity, ..Default::default() };
+    let mut cached: HashSet<String> = HashSet::new();
+    for access in accesses {
+        if access.op == Op::Delete {
+            continue;
+        }
+
+        if access.op == Op::Get {
+            report.requests += 1;
+            if cache.get(&access.key).is_some() {
+                report.hits += 1;
+                report.decisions.push(Decision::Hit);
+                continue;
+            }
+        } else if cache.contains(&access.key) {
+            cache.insert(&access.key, access.size);
+            report.decisions.push(Decision::Hit);
+            continue;
+        }
+
+        cache.insert(&access.key, access.size);
+        let decision = match cache.contains(&access.key) {
+            false => Decision::Bypass,
+            true if cache.len() > cached.len() => Decision::Insert,
+            true => {
+                let evicted = cached.iter().find(|key| !cache.contains(key)).cloned().unwrap_or_default();
+                cached.remove(&evicted);
+                Decision::Replace(evicted)
+            }
+        };
+        if decision != Decision::Bypass {
+            cached.insert(access.key.clone());
+        }
+        report.decisions.push(decision);
+    }
+
+    report
+}
+
+/// Computes the decisions of Belady's OPT, giving the best hit ratio reachable with the given capacity
+pub fn evaluate_opt(capacity: usize, accesses: &[Access]) -> DecisionReport {
+    let mut cache = Belady::new(capacity);
+
+    record(&mut cache, capacity, accesses)
+}
+
+/// Replays the accesses against the cache
+/// Reads that miss are followed by an insert of the access' size, as a demand filled cache would do
+/// Writes insert directly and deletes are skipped
+/// Sizes are given to size aware caches and used for the byte hit ratio
+pub fn simulate<C: IReplacementCache>(cache: &mut C, accesses: &[Access]) -> SimulationResult {
+    let keys: Vec<&str> = accesses
+        .iter()
+        .filter(|access| access.op != Op::Delete)
+        .map(|access| access.key.as_str())
+        .collect();
+    cache.prepare(&keys);
+
+    let mut result = SimulationResult { name: cache.name().to_string(), ..Default::default() };
+    for access in accesses {
+        match access.op {
+            Op::Get => {
+                result.requests += 1;
+                result.bytes += access.size as u64;
+                match cache.get(&access.key) {
+                    Some(_) => {
+                        result.hits += 1;
+                        result.hit_bytes += access.size as u64;
+                    },
+                    None => cache.insert_sized(&access.key, access.size, access.size)
+                }
+            },
+            Op::Set => {
+                result.writes += 1;
+                cache.insert_sized(&access.key, access.size, access.size);
+            },
+            Op::Delete => {}
+        }
+    }
+
+    result
+}
+
+/// Instantiates a cache of the given capacity and replays the accesses against it
+/// Fails on a capacity of zero, which LeCaR cannot have, so that every cache is compared at the capacity asked for
+pub fn run<C: IReplacementCache>(capacity: usize, accesses: &[Access]) -> Result<SimulationResult, LecarError> {
+    if capacity == 0 {
+        return Err(LecarError::InvalidConfig("simulated caches need a capacity of at least 1".to_string()));
+    }
+    let mut cache = C::new(capacity);
+
+    Ok(simulate(&mut cache, accesses))
+}
+
+/// Replays the accesses against LeCaR and every baseline with the same capacity
+/// LeCaR is sized as in the paper, with both histories as large as the main cache
+/// Fails on a capacity of zero
+pub fn compare(capacity: usize, accesses: &[Access]) -> Result<Vec<SimulationResult>, LecarError> {
+    Ok(vec![
+        run::<Controller>(capacity, accesses)?,
+        run::<Lru>(capacity, accesses)?,
+        run::<Lfu>(capacity, accesses)?,
+        run::<Arc>(capacity, accesses)?,
+        run::<Lirs>(capacity, accesses)?,
+        run::<TwoQ>(capacity, accesses)?,
+        run::<WTinyLfu>(capacity, accesses)?,
+        run::<Belady>(capacity, accesses)?
+    ])
+}
@@ -0,0 +1,178 @@
+use serde::{Deserialize, Serialize};
+
+/// Number of hash rows in the sketch
+const DEPTH: usize = 4;
+
+/// Largest value a counter can hold, counters are 4 bits wide as in TinyLFU
+const MAX_COUNT: u8 = 15;
+
+/// Number of hash functions of the doorkeeper
+const DOORKEEPER_HASHES: usize = 3;
+
+/// Bits of the doorkeeper per tracked key
+const DOORKEEPER_BITS_PER_KEY: usize = 8;
+
+/// FNV-1a offset basis and prime, 64 bits wide
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Returns the hash of a key for the given seed
+/// The counters are part of snapshots, so the hash is FNV-1a, which never changes across Rust releases unlike `DefaultHasher`
+/// Its output goes through the SplitMix64 finalizer, as only the low bits are kept
+fn hash(key: &str, seed: usize) -> usize {
+    let mut hash = (seed as u64).to_le_bytes().iter().chain(key.as_bytes())
+        .fold(FNV_OFFSET_BASIS, |hash, byte| (hash ^ *byte as u64).wrapping_mul(FNV_PRIME));
+
+    hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+
+    (hash ^ (hash >> 31)) as usize
+}
+
+/// Count-min sketch estimating how often keys were seen
+/// Counters are halved once the number of increments reaches the sample size so that old popularity fades
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CountMinSketch {
+    rows: Vec<Vec<u8>>,
+    mask: usize,
+    additions: usize,
+    sample_size: usize
+}
+
+impl CountMinSketch {
+    /// Instantiates a new sketch sized for tracking roughly the given number of keys
+    pub fn new(capacity: usize) -> Self {
+        Self::with_sample_size(capacity, capacity.max(1) * 10)
+    }
+
+    /// Instantiates a new sketch halving its counters after the given number of increments
+    pub fn with_sample_size(capacity: usize, sample_size: usize) -> Self {
+        let width = capacity.max(1).next_power_of_two();
+
+        Self {
+            rows: vec![vec![0; width]; DEPTH],
+            mask: width - 1,
+            additions: 0,
+            sample_size
+        }
+    }
+
+    /// Returns the counter index of a key for the given row
+    fn index(&self, key: &str, row: usize) -> usize {
+        hash(key, row) & self.mask
+    }
+
+    /// Records an occurrence of the key
+    /// Halves every counter once the sample size is reached
+    pub fn increment(&mut self, key: &str) {
+        for row in 0..DEPTH {
+            let index = self.index(key, row);
+            let counter = &mut self.rows[row][index];
+            *counter = (*counter + 1).min(MAX_COUNT);
+        }
+
+        self.additions = self.additions.saturating_add(1);
+        if self.additions >= self.sample_size {
+            self.reset();
+        }
+    }
+
+    /// Estimates how often the key was seen
+    pub fn estimate(&self, key: &str) -> u8 {
+        (0..DEPTH)
+            .map(|row| self.rows[row][self.index(key, row)])
+            .min()
+            .unwrap_or_default()
+    }
+
+    /// Halves every counter
+    pub fn reset(&mut self) {
+        self.rows.iter_mut().flatten().for_each(|counter| *counter /= 2);
+        self.additions /= 2;
+    }
+
+    /// Verifies the sketch has one row per hash, every row as wide as a power of two covered by the mask
+    /// And no counter over the largest count
+    pub fn check_invariants(&self) -> Result<(), String> {
+        let width = width(self.mask).ok_or_else(|| format!("sketch mask {} does not describe a power of two width", self.mask))?;
+
+        if self.rows.len() != DEPTH {
+            return Err(format!("sketch has {} rows instead of {}", self.rows.len(), DEPTH));
+        }
+
+        if let Some(row) = self.rows.iter().find(|row| row.len() != width) {
+            return Err(format!("sketch row holds {} counters instead of {}", row.len(), width));
+        }
+
+        match self.rows.iter().flatten().find(|counter| **counter > MAX_COUNT) {
+            Some(counter) => Err(format!("sketch counter {} is over {}", counter, MAX_COUNT)),
+            None => Ok(())
+        }
+    }
+}
+
+/// Returns the width described by a mask if it is a power of two
+fn width(mask: usize) -> Option<usize> {
+    mask.checked_add(1).filter(|width| width.is_power_of_two())
+}
+
+/// Bloom filter remembering which keys were seen once since the last reset
+/// Keeps one-hit wonders out of the count-min sketch
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Doorkeeper {
+    bits: Vec<u64>,
+    mask: usize
+}
+
+impl Doorkeeper {
+    /// Instantiates a new doorkeeper sized for roughly the given number of keys
+    pub fn new(capacity: usize) -> Self {
+        let width = (capacity.max(1) * DOORKEEPER_BITS_PER_KEY).next_power_of_two().max(64);
+
+        Self {
+            bits: vec![0; width / 64],
+            mask: width - 1
+        }
+    }
+
+    /// Returns the bit positions of a key
+    fn positions(&self, key: &str) -> [usize; DOORKEEPER_HASHES] {
+        let mut positions = [0; DOORKEEPER_HASHES];
+        for (seed, position) in positions.iter_mut().enumerate() {
+            *position = hash(key, DEPTH + seed) & self.mask;
+        }
+
+        positions
+    }
+
+    /// Returns whether the key may have been seen, false positives aside
+    pub fn contains(&self, key: &str) -> bool {
+        self.positions(key).iter().all(|bit| self.bits[bit / 64] & (1 << (bit % 64)) != 0)
+    }
+
+    /// Remembers the key
+    /// Returns whether it may have been seen already
+    pub fn insert(&mut self, key: &str) -> bool {
+        let seen = self.contains(key);
+        for bit in self.positions(key).iter() {
+            self.bits[bit / 64] |= 1 << (bit % 64);
+        }
+
+        seen
+    }
+
+    /// Forgets every key
+    pub fn clear(&mut self) {
+        self.bits.iter_mut().for_each(|word| *word = 0);
+    }
+
+    /// Verifies the bits cover exactly the power of two width described by the mask
+    pub fn check_invariants(&self) -> Result<(), String> {
+        let width = width(self.mask).ok_or_else(|| format!("doorkeeper mask {} does not describe a power of two width", self.mask))?;
+
+        match self.bits.len().checked_mul(64) {
+            Some(bits) if bits == width => Ok(()),
+            _ => Err(format!("doorkeeper holds {} words for a width of {} bits", self.bits.len(), width))
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use crate::baselines::{Arc, Belady, Lfu, Lirs, Lru, TwoQ, WTinyLfu};
+use crate::cache::{ICache, IReplacementCache};
+use crate::controller::Controller;
+use crate::error::LecarError;
+use crate::simulator::{compare, evaluate_opt, record, run, Decision};
+use crate::trace::{Access, Op};
+use crate::workload::Workload;
+
+fn check_capacity<C: IReplacementCache>() {
+    for capacity in [0, 1, 10] {
+        let mut cache = C::new(capacity);
+        for access in (Workload::Zipf { keys: 50, exponent: 0.9 }).generate(1_000, 3) {
+            if cache.get(&access.key).is_none() {
+                cache.insert(&access.key, access.size);
+            }
+            assert!(cache.len() <= capacity, "{} exceeded its capacity", cache.name());
+        }
+    }
+}
+
+#[test]
+fn test_baselines_capacity() {
+    check_capacity::<Lru>();
+    check_capacity::<Lfu>();
+    check_capacity::<Arc>();
+    check_capacity::<Lirs>();
+    check_capacity::<TwoQ>();
+    check_capacity::<WTinyLfu>();
+    check_capacity::<Belady>();
+}
+
+#[test]
+fn test_baselines_eviction_order() {
+    let mut lru = Lru::new(2);
+    lru.insert("a", 1);
+    lru.insert("b", 2);
+    lru.get("a");
+    lru.insert("c", 3);
+    assert!(lru.contains("a") && !lru.contains("b"));
+
+    let mut lfu = Lfu::new(2);
+    lfu.insert("a", 1);
+    lfu.insert("b", 2);
+    lfu.get("a");
+    lfu.get("b");
+    lfu.get("b");
+    lfu.insert("c", 3);
+    assert!(lfu.contains("b") && !lfu.contains("a"));
+
+    let mut opt = Belady::new(2);
+    opt.prepare(&["a", "b", "c", "a", "b"]);
+    for key in ["a", "b", "c"] {
+        if opt.get(key).is_none() {
+            opt.insert(key, 0);
+        }
+    }
+    assert!(opt.contains("a") && opt.contains("b") && !opt.contains("c"));
+}
+
+#[test]
+fn test_simulator_compare() {
+    let accesses = Workload::Phases(vec![
+        (Workload::Zipf { keys: 300, exponent: 1.0 }, 3_000),
+        (Workload::ScanHeavy { keys: 300, exponent: 1.0, scan_every: 200, scan_length: 100 }, 3_000)
+    ]).generate(6_000, 5);
+
+    let results = compare(50, &accesses).unwrap();
+    let names: Vec<&str> = results.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(names, vec!["LeCaR", "LRU", "LFU", "ARC", "LIRS", "2Q", "W-TinyLFU", "OPT"]);
+
+    let optimal = run::<Belady>(50, &accesses).unwrap();
+    for result in &results {
+        assert_eq!(result.requests, 6_000);
+        assert!(result.hits <= optimal.hits, "{} beat OPT", result.name);
+        // Every access has a size of 1
+        assert_eq!((result.bytes, result.hit_bytes), (result.requests, result.hits));
+        assert_eq!(result.byte_hit_ratio(), result.hit_ratio());
+    }
+
+    // LeCaR cannot have a capacity of zero, so no cache is compared at that capacity
+    assert!(matches!(compare(0, &accesses), Err(LecarError::InvalidConfig(_))));
+}
+
+
+#[test]
+fn test_opt_decisions() {
+    let accesses: Vec<Access> = ["a", "b", "c", "a", "b", "c", "d", "c"]
+        .iter()
+        .enumerate()
+        .map(|(i, key)| Access { key: key.to_string(), op: Op::Get, size: 1, timestamp: i as u64 })
+        .collect();
+
+    let report = evaluate_opt(2, &accesses);
+    assert_eq!(report.decisions, vec![
+        Decision::Insert,
+        Decision::Insert,
+        Decision::Bypass,
+        Decision::Hit,
+        Decision::Hit,
+        Decision::Replace("b".to_string()),
+        Decision::Bypass,
+        Decision::Hit
+    ]);
+    assert_eq!(report.hits, 3);
+
+    let accesses = (Workload::Zipf { keys: 200, exponent: 0.9 }).generate(3_000, 9);
+    let optimal = evaluate_opt(40, &accesses);
+    let lecar = record(&mut <Controller as ICache>::new(40), 40, &accesses);
+    assert_eq!(optimal.hit_ratio(), run::<Belady>(40, &accesses).unwrap().hit_ratio());
+    assert!(lecar.hit_ratio() <= optimal.hit_ratio());
+    assert!(lecar.agreement(&optimal) > 0.0 && lecar.agreement(&optimal) < 1.0);
+    assert_eq!(lecar.agreement(&lecar), 1.0);
+}
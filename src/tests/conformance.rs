@@ -0,0 +1,253 @@
+//! Conformance of the paper faithful mode with Algorithm 1 of Vietri et al.
+//!
+//! `Reference` is a direct transcription of the published pseudocode, kept independent from the controller.
+//! Sharing the controller's seeded RNG, it must make exactly the same decisions, which catches divergences in the controller
+//! But not mistakes in the transcription itself.
+//!
+//! That takes the authors' own implementation: export the traces with `cargo test export_conformance_traces -- --ignored`,
+//! Replay them through the published LeCaR code and record its hit counts and LFU weight every `SAMPLE_EVERY` requests,
+//! With the source and revision used, in `src/tests/fixtures/published_conformance.json`.
+//! `test_published_conformance` then compares hit ratios and weight trajectories within a tolerance,
+//! As both implementations draw their policies from different random generators.
+//!
+//! Those fixtures have not been recorded yet, so the test is ignored and conformance with the published code is unverified.
+
+use crate::controller::{Controller, ControllerConfig};
+use crate::workload::Workload;
+use rand::RngCore;
+use rand_chacha::rand_core::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::f64::consts::E;
+
+const PUBLISHED_FIXTURES: &str = "src/tests/fixtures/published_conformance.json";
+
+/// Directory the traces are exported to, one key per line
+const TRACES: &str = "target/conformance";
+
+/// Largest difference in hit ratio allowed with the authors' implementation
+const HIT_RATIO_TOLERANCE: f64 = 0.02;
+
+/// Largest mean difference in LFU weight allowed with the authors' implementation, over every sample of a trace
+const WEIGHT_TOLERANCE: f64 = 0.1;
+
+/// Number of requests between two recorded weights
+const SAMPLE_EVERY: usize = 100;
+
+#[derive(Debug, PartialEq)]
+struct Fixture {
+    name: String,
+    cache_size: usize,
+    requests: usize,
+    hits: usize,
+    lfu_weights: Vec<f64>
+}
+
+/// Hit counts and weight trajectories recorded by replaying the exported traces through the authors' implementation
+#[derive(Serialize, Deserialize, Debug)]
+struct PublishedFixtures {
+    /// Where the implementation was obtained from
+    source: String,
+    /// Revision of the implementation that was run
+    revision: String,
+    cases: Vec<PublishedCase>
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct PublishedCase {
+    name: String,
+    cache_size: usize,
+    requests: usize,
+    hits: usize,
+    /// LFU weight after every `SAMPLE_EVERY` requests
+    lfu_weights: Vec<f64>
+}
+
+fn cases() -> Vec<(&'static str, usize, Workload)> {
+    vec![
+        ("zipf", 50, Workload::Zipf { keys: 500, exponent: 0.9 }),
+        ("loop", 100, Workload::Loop { keys: 120 }),
+        ("scan_heavy", 60, Workload::ScanHeavy { keys: 300, exponent: 1.0, scan_every: 150, scan_length: 80 }),
+        ("shifting_hotset", 40, Workload::ShiftingHotset { keys: 2_000, hot_keys: 30, hot_probability: 0.9, shift_every: 1_000 }),
+        ("phases", 80, Workload::Phases(vec![
+            (Workload::Loop { keys: 100 }, 1_500),
+            (Workload::Zipf { keys: 400, exponent: 1.1 }, 1_500)
+        ]))
+    ]
+}
+
+fn trace(workload: &Workload) -> Vec<String> {
+    workload.generate(5_000, 2018).into_iter().map(|access| access.key).collect()
+}
+
+/// Algorithm 1 of the paper
+/// Ties between equally frequent items are ejected least recently used first
+struct Reference {
+    size: usize,
+    time: u64,
+    // key -> (frequency, last request)
+    cache: HashMap<String, (usize, u64)>,
+    // (key, frequency, eviction time), oldest first
+    lru_history: VecDeque<(String, usize, u64)>,
+    lfu_history: VecDeque<(String, usize, u64)>,
+    w_lru: f64,
+    w_lfu: f64,
+    learning_rate: f64,
+    discount_rate: f64,
+    rng: ChaCha8Rng
+}
+
+impl Reference {
+    fn new(size: usize) -> Self {
+        Self {
+            size,
+            time: 0,
+            cache: HashMap::new(),
+            lru_history: VecDeque::new(),
+            lfu_history: VecDeque::new(),
+            w_lru: 0.5,
+            w_lfu: 0.5,
+            learning_rate: 0.45,
+            discount_rate: 0.005f64.powf(1.0 / size as f64),
+            rng: ChaCha8Rng::seed_from_u64(10)
+        }
+    }
+
+    fn take(history: &mut VecDeque<(String, usize, u64)>, key: &str) -> Option<(usize, u64)> {
+        let position = history.iter().position(|(k, _, _)| k == key)?;
+
+        history.remove(position).map(|(_, frequency, evicted_at)| (frequency, evicted_at))
+    }
+
+    /// Returns whether the request hit
+    fn request(&mut self, key: &str) -> bool {
+        self.time += 1;
+
+        if let Some((frequency, last)) = self.cache.get_mut(key) {
+            *frequency += 1;
+            *last = self.time;
+            return true;
+        }
+
+        let mut frequency = 0;
+        if let Some((old_frequency, evicted_at)) = Self::take(&mut self.lru_history, key) {
+            let reward = self.discount_rate.powf((self.time - evicted_at) as f64);
+            self.w_lfu *= E.powf(self.learning_rate * reward);
+            frequency = old_frequency + 1;
+        } else if let Some((old_frequency, evicted_at)) = Self::take(&mut self.lfu_history, key) {
+            let reward = self.discount_rate.powf((self.time - evicted_at) as f64);
+            self.w_lru *= E.powf(self.learning_rate * reward);
+            frequency = old_frequency + 1;
+        }
+        let total = self.w_lru + self.w_lfu;
+        self.w_lfu /= total;
+        self.w_lru = 1.0 - self.w_lfu;
+
+        if self.cache.len() == self.size {
+            let p = self.rng.next_u32() as f64 / u32::MAX as f64;
+            let (victim, history) = if p <= self.w_lfu {
+                let victim = self.cache.iter().min_by_key(|(_, (f, last))| (*f, *last)).map(|(k, _)| k.clone());
+                (victim, &mut self.lfu_history)
+            } else {
+                let victim = self.cache.iter().min_by_key(|(_, (_, last))| *last).map(|(k, _)| k.clone());
+                (victim, &mut self.lru_history)
+            };
+
+            if let Some(victim) = victim {
+                let (victim_frequency, _) = self.cache.remove(&victim).unwrap();
+                if history.len() == self.size {
+                    history.pop_front();
+                }
+                history.push_back((victim, victim_frequency, self.time));
+            }
+        }
+
+        self.cache.insert(key.to_string(), (frequency, self.time));
+        false
+    }
+}
+
+/// Replays the trace, recording the hits and the LFU weight every SAMPLE_EVERY requests
+fn replay<F: FnMut(&str) -> (bool, f64)>(name: &str, cache_size: usize, keys: &[String], mut request: F) -> Fixture {
+    let mut fixture = Fixture { name: name.to_string(), cache_size, requests: keys.len(), hits: 0, lfu_weights: vec![] };
+
+    for (i, key) in keys.iter().enumerate() {
+        let (hit, lfu_weight) = request(key);
+        fixture.hits += hit as usize;
+        if (i + 1) % SAMPLE_EVERY == 0 {
+            fixture.lfu_weights.push(lfu_weight);
+        }
+    }
+
+    fixture
+}
+
+fn reference_fixture(name: &str, cache_size: usize, keys: &[String]) -> Fixture {
+    let mut reference = Reference::new(cache_size);
+
+    replay(name, cache_size, keys, |key| (reference.request(key), reference.w_lfu))
+}
+
+fn controller_fixture(name: &str, cache_size: usize, keys: &[String]) -> Fixture {
+    let mut controller = Controller::with_config(ControllerConfig::paper(cache_size)).unwrap();
+
+    replay(name, cache_size, keys, |key| {
+        let hit = controller.get(key).is_some();
+        if !hit {
+            controller.insert(key, 0);
+        }
+        (hit, controller.lfu_prob())
+    })
+}
+
+fn assert_matches(actual: &Fixture, expected: &Fixture) {
+    assert_eq!(actual.name, expected.name);
+    assert_eq!(actual.hits, expected.hits, "hit count of {}", expected.name);
+    assert_eq!(actual.lfu_weights.len(), expected.lfu_weights.len());
+    for (i, (a, e)) in actual.lfu_weights.iter().zip(&expected.lfu_weights).enumerate() {
+        assert!((a - e).abs() < 1e-9, "{}: LFU weight after {} requests is {}, expected {}", expected.name, (i + 1) * SAMPLE_EVERY, a, e);
+    }
+}
+
+#[test]
+fn test_paper_faithful_matches_transcription() {
+    for (name, cache_size, workload) in cases() {
+        let keys = trace(&workload);
+        assert_matches(&controller_fixture(name, cache_size, &keys), &reference_fixture(name, cache_size, &keys));
+    }
+}
+
+#[test]
+#[ignore = "needs src/tests/fixtures/published_conformance.json, recorded from the authors' implementation"]
+fn test_published_conformance() {
+    let path = format!("{}/{}", env!("CARGO_MANIFEST_DIR"), PUBLISHED_FIXTURES);
+    let fixtures: PublishedFixtures = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+    assert!(!fixtures.source.is_empty() && !fixtures.revision.is_empty(), "the fixtures do not record where they come from");
+
+    for (name, cache_size, workload) in cases() {
+        let expected = fixtures.cases.iter().find(|case| case.name == name).unwrap();
+        let keys = trace(&workload);
+        assert_eq!((expected.cache_size, expected.requests), (cache_size, keys.len()), "{} was recorded on another case", name);
+
+        let actual = controller_fixture(name, cache_size, &keys);
+        let difference = (actual.hits as f64 - expected.hits as f64).abs() / keys.len() as f64;
+        assert!(difference <= HIT_RATIO_TOLERANCE, "{}: {} hits, the authors' implementation has {}", name, actual.hits, expected.hits);
+
+        assert_eq!(actual.lfu_weights.len(), expected.lfu_weights.len(), "{} was sampled every {} requests", name, SAMPLE_EVERY);
+        let drift = actual.lfu_weights.iter().zip(&expected.lfu_weights).map(|(a, e)| (a - e).abs()).sum::<f64>() / actual.lfu_weights.len() as f64;
+        assert!(drift <= WEIGHT_TOLERANCE, "{}: LFU weights differ by {} on average from the authors' implementation", name, drift);
+    }
+}
+
+#[test]
+#[ignore]
+fn export_conformance_traces() {
+    let directory = format!("{}/{}", env!("CARGO_MANIFEST_DIR"), TRACES);
+    std::fs::create_dir_all(&directory).unwrap();
+
+    for (name, cache_size, workload) in cases() {
+        let path = format!("{}/{}_{}.trace", directory, name, cache_size);
+        std::fs::write(path, trace(&workload).join("\n") + "\n").unwrap();
+    }
+}
@@ -0,0 +1,622 @@
+use crate::cache::{Cache, CacheItem, FrequencyAging, ICache, Policy};
+use crate::controller::{Controller, ControllerConfig, Entry, GetResult, InsertOptions, Location, Objective, Removal, Weights};
+use crate::error::LecarError;
+use crate::workload::Workload;
+use indexmap::IndexMap;
+use std::collections::BTreeSet;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn test_peek() {
+    let mut controller = Controller::new(2, 2, 2).unwrap();
+    controller.insert("a", 1);
+    controller.insert("b", 2);
+    controller.insert("c", 3);
+
+    let evicted = ["a", "b"].iter().copied().find(|key| controller.get_index_of(key).is_none()).unwrap();
+    let decode = |controller: &Controller| serde_json::from_slice::<serde_json::Value>(&controller.to_snapshot().unwrap()).unwrap();
+    let before = decode(&controller);
+
+    assert_eq!(controller.peek("c", false), Some((3, Location::Main)));
+    assert_eq!(controller.peek(evicted, false), None);
+    assert!(matches!(controller.peek(evicted, true), Some((_, Location::History(Policy::LFU | Policy::LRU)))));
+    assert_eq!(controller.peek("d", true), None);
+
+    // Nothing, including the recency of the items and the RNG, changed
+    assert_eq!(decode(&controller), before);
+}
+
+#[test]
+fn test_get_detailed() {
+    let mut controller = Controller::new(1, 1, 1).unwrap();
+    controller.insert("a", 1);
+    controller.insert("b", 2);
+
+    assert_eq!(controller.get_detailed("b"), GetResult::MainHit(2));
+    assert_eq!(controller.get_detailed("c"), GetResult::Miss);
+    match controller.get_detailed("a") {
+        GetResult::HistoryHit { value, regret, .. } => {
+            assert_eq!(value, 1);
+            assert!(regret > 0.0 && regret <= 1.0);
+        },
+        other => panic!("expected a history hit, got {:?}", other)
+    }
+    assert_eq!(controller.peek("a", false), Some((1, Location::Main)));
+}
+
+#[test]
+fn test_iteration() {
+    let mut controller = Controller::with_config(ControllerConfig::paper(2)).unwrap();
+    for key in ["a", "b", "a", "c", "d", "e"].iter() {
+        if controller.get(key).is_none() {
+            controller.insert(key, key.len() as u32);
+        }
+    }
+
+    assert_eq!(controller.keys().count(), 2);
+    assert_eq!(controller.values().sum::<u32>(), 2);
+    assert!(controller.iter().all(|(key, _)| controller.peek(key, false).is_some()));
+
+    // Three keys were ejected, oldest first, into histories of two items
+    let history: Vec<_> = controller.lfu_history().chain(controller.lru_history()).collect();
+    assert_eq!(history.len(), 3);
+    for entries in [controller.lfu_history().collect::<Vec<_>>(), controller.lru_history().collect()].iter() {
+        assert!(entries.windows(2).all(|pair| pair[0].age >= pair[1].age));
+    }
+}
+
+#[test]
+fn test_entry() {
+    let mut controller = Controller::new(1, 1, 1).unwrap();
+
+    *controller.entry("a").or_insert(1) += 1;
+    assert_eq!(controller.peek("a", false), Some((2, Location::Main)));
+    controller.entry("a").and_modify(|value| *value *= 10).or_insert(0);
+    assert_eq!(controller.peek("a", false), Some((20, Location::Main)));
+
+    // "a" is ejected into a policy cache, its entry brings it back with a single weight update
+    assert_eq!(*controller.entry("b").or_insert_with(|| 3), 3);
+    let weights = controller.lfu_prob();
+    match controller.entry("a") {
+        Entry::Occupied(mut entry) => assert_eq!(entry.insert(21), 20),
+        Entry::Vacant(_) => panic!("expected the entry to be occupied")
+    }
+    assert_ne!(controller.lfu_prob(), weights);
+    assert_eq!(controller.peek("a", false), Some((21, Location::Main)));
+    assert!(controller.check_invariants().is_ok());
+}
+
+#[test]
+fn test_batch_operations() {
+    let mut controller = Controller::new(4, 4, 4).unwrap();
+
+    let results = controller.insert_many(&[("a", 1), ("b", 2), ("a", 3), ("c", 4), ("d", 5), ("e", 6), ("f", 7)]);
+    assert_eq!(results[..3], [GetResult::Miss, GetResult::Miss, GetResult::MainHit(1)]);
+    assert_eq!(controller.len().0, 4);
+    assert!(controller.check_invariants().is_ok());
+
+    // Two of the first keys were ejected together into the same policy cache
+    let (_, lfu, lru) = controller.len();
+    assert!((lfu, lru) == (2, 0) || (lfu, lru) == (0, 2));
+
+    let keys = ["a", "b", "c", "d", "e", "f", "g"];
+    let results = controller.get_many(&keys);
+    assert_eq!(results.iter().filter(|result| matches!(result, GetResult::MainHit(_))).count(), 4);
+    assert_eq!(results.iter().filter(|result| matches!(result, GetResult::HistoryHit { .. })).count(), 2);
+    assert_eq!(results[6], GetResult::Miss);
+    assert_eq!((controller.counter.num_queries, controller.counter.hits, controller.counter.misses), (7, 6, 1));
+    assert_eq!(controller.get("g"), None);
+    assert_eq!(controller.get("f"), Some(7));
+    assert_eq!((controller.counter.num_queries, controller.counter.hits, controller.counter.misses), (9, 7, 2));
+    assert!(controller.check_invariants().is_ok());
+
+    let removed = controller.remove_many(&["a", "g"]);
+    assert!(matches!(removed[0], Some((3, _))));
+    assert_eq!(removed[1], None);
+    assert_eq!(controller.peek("a", true), None);
+    assert!(controller.check_invariants().is_ok());
+
+    // A miss followed by the insert of the missed key counts as a single request, batched or not
+    let clock = |controller: &Controller| serde_json::from_slice::<serde_json::Value>(&controller.to_snapshot().unwrap()).unwrap()["clock"].clone();
+    let mut single = Controller::new(4, 4, 4).unwrap();
+    single.get("x");
+    single.insert("x", 1);
+    let mut batched = Controller::new(4, 4, 4).unwrap();
+    batched.get_many(&["w", "x"]);
+    batched.insert_many(&[("x", 1)]);
+    assert_eq!((clock(&single), single.counter.missed_bytes), (serde_json::json!(1), 1));
+    assert_eq!((clock(&batched), batched.counter.missed_bytes), (serde_json::json!(2), 1));
+}
+
+#[test]
+fn test_pinning() {
+    let mut controller = Controller::new(3, 3, 3).unwrap();
+    controller.insert("config", 1);
+    controller.insert("schema", 2);
+    controller.insert("other", 3);
+
+    assert!(matches!(controller.pin("missing"), Err(LecarError::KeyNotFound(_))));
+    assert!(controller.pin("config").is_ok());
+    assert!(controller.pin("config").is_ok());
+    assert!(controller.pin("schema").is_ok());
+    assert!(matches!(controller.pin("other"), Err(LecarError::FullyPinned(_))));
+    assert_eq!(controller.pinned_len(), 2);
+
+    for key in 0..20u32 {
+        controller.insert(&key.to_string(), key);
+    }
+    controller.insert_many(&[("a", 1), ("b", 2), ("c", 3)]);
+    assert_eq!(controller.peek("config", false), Some((1, Location::Main)));
+    assert_eq!(controller.peek("schema", false), Some((2, Location::Main)));
+    assert_eq!(controller.peek("c", false), Some((3, Location::Main)));
+    assert!(controller.check_invariants().is_ok());
+
+    assert!(controller.unpin("config"));
+    assert!(!controller.unpin("config"));
+    assert_eq!(controller.pinned_len(), 1);
+}
+
+#[test]
+fn test_victims() {
+    let mut cache: Cache<IndexMap<String, CacheItem>> = ICache::new(10);
+    for key in 0..10usize {
+        cache.insert(CacheItem::new(key.to_string(), key as u32));
+        (0..(key * 7) % 10).for_each(|_| { cache.get(&key.to_string()); });
+    }
+    let keys = |victims: Vec<&CacheItem>| victims.iter().map(|item| item.key().to_string()).collect::<Vec<_>>();
+
+    // A partial selection finds the same victims, in the same order, as sorting every item
+    let all = keys(cache.victims(10, Policy::LFU));
+    assert_eq!(all, vec!["0", "3", "6", "9", "2", "5", "8", "1", "4", "7"]);
+    for count in 0..=12 {
+        assert_eq!(keys(cache.victims(count, Policy::LFU)), all[..count.min(10)]);
+    }
+
+    let ejected: Vec<String> = cache.eject_many(3, Policy::LFU).iter().map(|item| item.key().to_string()).collect();
+    assert_eq!(ejected, all[..3]);
+    assert_eq!(cache.len(), 7);
+}
+
+#[test]
+fn test_cost_aware() {
+    // GreedyDual ejects the cheapest item, raising the inflation so that unused costly items eventually go
+    let mut cache: Cache<IndexMap<String, CacheItem>> = ICache::new(2);
+    cache.insert(CacheItem::with_cost("slow".to_string(), 1, 3));
+    cache.insert(CacheItem::with_cost("fast".to_string(), 2, 1));
+    let ejected = cache.insert_with_policy(CacheItem::with_cost("a".to_string(), 3, 1), Policy::GDS);
+    assert_eq!(ejected.map(|item| item.key().to_string()), Some("fast".to_string()));
+    let ejected = cache.insert_with_policy(CacheItem::with_cost("b".to_string(), 4, 1), Policy::GDS);
+    assert_eq!(ejected.map(|item| item.key().to_string()), Some("a".to_string()));
+    let ejected = cache.insert_with_policy(CacheItem::with_cost("c".to_string(), 5, 1), Policy::GDS);
+    assert_eq!(ejected.map(|item| item.key().to_string()), Some("slow".to_string()));
+
+    // Credits are only totally ordered while finite
+    assert!(cache.check_invariants().is_ok());
+    cache.peek_mut("c").unwrap().refresh_credit(f64::NAN);
+    assert!(cache.check_invariants().is_err());
+
+    // The regret of a history hit is scaled by the cost of the missed item
+    let mut controller = Controller::new(1, 1, 1).unwrap();
+    controller.insert_with_options("slow", 1, InsertOptions { cost: Some(100), ..Default::default() });
+    controller.insert("fast", 2);
+    let slow = match controller.get_detailed("slow") {
+        GetResult::HistoryHit { regret, .. } => regret,
+        other => panic!("expected a history hit, got {:?}", other)
+    };
+    let fast = match controller.get_detailed("fast") {
+        GetResult::HistoryHit { regret, .. } => regret,
+        other => panic!("expected a history hit, got {:?}", other)
+    };
+    assert!(slow > 0.9 && fast < 0.02);
+
+    // The expert is consulted once enabled
+    let mut config = ControllerConfig::new(8, 8, 8);
+    config.gds_cache_size = 8;
+    let mut controller = Controller::with_config(config).unwrap();
+    for key in 0..200u32 {
+        controller.insert_with_options(&(key % 40).to_string(), key, InsertOptions { cost: Some(key % 7), ..Default::default() });
+    }
+    assert!(controller.gds_len() > 0);
+    assert!(controller.check_invariants().is_ok());
+}
+
+#[test]
+fn test_byte_hit_ratio() {
+    let mut config = ControllerConfig::new(1, 1, 1);
+    config.objective = Objective::ByteHitRatio;
+    let mut controller = Controller::with_config(config).unwrap();
+
+    // The regret of a history hit is scaled by the size of the missed item
+    controller.insert_with_options("large", 1, InsertOptions { size: Some(1_000), ..Default::default() });
+    controller.insert_with_options("small", 2, InsertOptions { size: Some(10), ..Default::default() });
+    let large = match controller.get_detailed("large") {
+        GetResult::HistoryHit { regret, .. } => regret,
+        other => panic!("expected a history hit, got {:?}", other)
+    };
+    let small = match controller.get_detailed("small") {
+        GetResult::HistoryHit { regret, .. } => regret,
+        other => panic!("expected a history hit, got {:?}", other)
+    };
+    assert!(large > 0.9 && small < 0.02);
+
+    // Hits are recorded, and so is a miss once the missed object is inserted
+    assert_eq!(controller.counter.hit_bytes, 1_010);
+    assert_eq!(controller.get("new"), None);
+    controller.insert_with_options("new", 3, InsertOptions { size: Some(3_030), ..Default::default() });
+    assert_eq!(controller.counter.byte_hit_ratio(), 0.25);
+
+    // Updating a value without options keeps the size of the object
+    controller.insert("new", 4);
+    assert_eq!(controller.get("new"), Some(4));
+    assert_eq!(controller.counter.hit_bytes, 4_040);
+}
+
+/// Replays a hot set of 20 keys interleaved with scans of one-hit wonders
+/// Returns the hits on the hot set
+fn replay_hot_set_and_scans(controller: &mut Controller) -> usize {
+    let mut hot_hits = 0;
+    for round in 0..50u32 {
+        for key in 0..20u32 {
+            let key = key.to_string();
+            match controller.get(&key) {
+                Some(_) => hot_hits += 1,
+                None => controller.insert(&key, 0)
+            }
+        }
+
+        for key in 0..40u32 {
+            let key = format!("scan:{}:{}", round, key);
+            if controller.get(&key).is_none() {
+                controller.insert(&key, 0);
+            }
+        }
+    }
+
+    hot_hits
+}
+
+#[test]
+fn test_admission() {
+    let mut config = ControllerConfig::new(20, 20, 20);
+    let without_admission = replay_hot_set_and_scans(&mut Controller::with_config(config.clone()).unwrap());
+    config.admission = true;
+    let mut controller = Controller::with_config(config).unwrap();
+    let with_admission = replay_hot_set_and_scans(&mut controller);
+
+    assert!(with_admission > 900 && with_admission > 2 * without_admission, "{} against {} hot hits", with_admission, without_admission);
+    assert!(controller.counter.rejected > 0);
+    assert!(controller.check_invariants().is_ok());
+
+    // Entries bypass the filter
+    assert_eq!(*controller.entry("new").or_insert(1), 1);
+
+    // A batch only filters the new keys that displace an item, as single inserts do
+    let mut config = ControllerConfig::new(4, 4, 4);
+    config.admission = true;
+    let warmed = || {
+        let mut controller = Controller::with_config(config.clone()).unwrap();
+        for key in ["a", "b", "c"] {
+            controller.insert(key, 0);
+            controller.get(key);
+        }
+        controller
+    };
+    let mut single = warmed();
+    single.insert("x", 1);
+    single.insert("y", 2);
+    let mut batch = warmed();
+    batch.insert_many(&[("x", 1), ("y", 2)]);
+    for controller in [&single, &batch] {
+        assert_eq!(controller.len().0, 4);
+        assert_eq!(controller.counter.admitted + controller.counter.rejected, 1);
+    }
+
+    let mut config = ControllerConfig::paper(20);
+    config.admission = true;
+    assert!(matches!(Controller::with_config(config), Err(LecarError::InvalidConfig(_))));
+}
+
+#[test]
+fn test_frequency_aging() {
+    let mut item = CacheItem::new("a".to_string(), 1);
+    (0..9).for_each(|_| item.touch());
+    item.age(FrequencyAging::Halving { period: 1 });
+    assert_eq!(item.frequency(), 4);
+    item.age(FrequencyAging::Decay { period: 1, factor: 0.5 });
+    assert_eq!(item.frequency(), 2);
+    (0..3).for_each(|_| item.touch());
+    item.age(FrequencyAging::Window { window: 1 });
+    assert_eq!(item.frequency(), 3);
+    item.touch();
+    assert_eq!(item.frequency(), 4);
+    item.age(FrequencyAging::Window { window: 1 });
+    assert_eq!(item.frequency(), 1);
+
+    // A key that was hot long ago loses its lead once its window has passed
+    let aging = FrequencyAging::Window { window: 10 };
+    let mut cache: Cache<IndexMap<String, CacheItem>> = ICache::new(2);
+    cache.insert(CacheItem::new("old".to_string(), 0));
+    (0..20).for_each(|_| { cache.get("old"); });
+    cache.age_frequencies(aging);
+    cache.insert(CacheItem::new("new".to_string(), 1));
+    (0..3).for_each(|_| { cache.get("new"); });
+    cache.age_frequencies(aging);
+    let ejected = cache.insert_with_policy(CacheItem::new("next".to_string(), 2), Policy::LFU);
+    assert_eq!(ejected.map(|item| item.key().to_string()), Some("old".to_string()));
+
+    let mut config = ControllerConfig::new(20, 20, 20);
+    config.frequency_aging = FrequencyAging::Halving { period: 50 };
+    let mut controller = Controller::with_config(config.clone()).unwrap();
+    replay_hot_set_and_scans(&mut controller);
+    assert!(controller.check_invariants().is_ok());
+
+    config.frequency_aging = FrequencyAging::Decay { period: 10, factor: 1.0 };
+    assert!(matches!(Controller::with_config(config.clone()), Err(LecarError::InvalidConfig(_))));
+    config.frequency_aging = FrequencyAging::Window { window: 0 };
+    assert!(matches!(Controller::with_config(config), Err(LecarError::InvalidConfig(_))));
+}
+
+#[test]
+fn test_deterministic_ties() {
+    // Items used as often are ejected least recently used first, whatever their place in the cache
+    let mut cache: Cache<IndexMap<String, CacheItem>> = ICache::new(3);
+    for key in ["a", "b", "c"] {
+        cache.insert(CacheItem::new(key.to_string(), 0));
+    }
+    cache.get("b");
+    cache.get("a");
+    cache.get("c");
+    cache.update("b", 1);
+    let ejected = cache.insert_with_policy(CacheItem::new("d".to_string(), 0), Policy::LFU);
+    assert_eq!(ejected.map(|item| item.key().to_string()), Some("a".to_string()));
+    let ejected = cache.insert_with_policy(CacheItem::new("e".to_string(), 0), Policy::LRU);
+    assert_eq!(ejected.map(|item| item.key().to_string()), Some("c".to_string()));
+
+    // Replicas fed the same requests stay in lockstep, even when their caches were filled in a different order
+    let accesses = (Workload::Loop { keys: 30 }).generate(2_000, 1);
+    let replicas: Vec<Controller> = (0..2)
+        .map(|replica| {
+            let mut controller = Controller::new(10, 10, 10).unwrap();
+            let mut warm: Vec<String> = (0..10).map(|key| format!("w{}", key)).collect();
+            if replica == 1 {
+                warm.reverse();
+            }
+            for key in &warm {
+                controller.insert(key, 0);
+            }
+            for key in 0..10 {
+                controller.get(&format!("w{}", key));
+            }
+            for access in &accesses {
+                if controller.get(&access.key).is_none() {
+                    controller.insert(&access.key, 0);
+                }
+            }
+            controller
+        })
+        .collect();
+
+    let state = |controller: &Controller| (
+        controller.keys().map(str::to_string).collect::<BTreeSet<_>>(),
+        controller.lfu_history().map(|entry| entry.key.to_string()).collect::<Vec<_>>(),
+        controller.lru_history().map(|entry| entry.key.to_string()).collect::<Vec<_>>()
+    );
+    assert_eq!(state(&replicas[0]), state(&replicas[1]));
+}
+
+#[test]
+fn test_resize() {
+    let mut controller = Controller::new(10, 10, 10).unwrap();
+    for key in 0..10u32 {
+        controller.insert(&key.to_string(), key);
+    }
+    controller.pin("0").unwrap();
+
+    assert!(controller.resize(6, 2, 2).is_ok());
+    assert_eq!(ICache::len(&controller), 6);
+    assert_eq!(controller.peek("0", false), Some((0, Location::Main)));
+    let evicted = (0..10u32).filter(|key| controller.peek(&key.to_string(), true).is_some_and(|(_, location)| location != Location::Main));
+    assert!(evicted.count() >= 2);
+    assert!(controller.lfu_history().count() <= 2 && controller.lru_history().count() <= 2);
+    assert!(controller.check_invariants().is_ok());
+
+    // Growing keeps the warm items
+    assert!(controller.resize(12, 4, 4).is_ok());
+    for key in 10..16u32 {
+        controller.insert(&key.to_string(), key);
+    }
+    assert_eq!(ICache::len(&controller), 12);
+    assert!(controller.check_invariants().is_ok());
+
+    assert!(matches!(controller.resize(0, 4, 4), Err(LecarError::InvalidConfig(_))));
+    assert!(matches!(controller.resize(1, 4, 4), Err(LecarError::InvalidConfig(_))));
+
+    let mut controller = Controller::with_config(ControllerConfig::paper(10)).unwrap();
+    for key in 0..20u32 {
+        controller.insert(&key.to_string(), key);
+    }
+    assert!(controller.resize(5, 3, 3).is_ok());
+    assert_eq!(ICache::len(&controller), 5);
+    assert!(controller.check_invariants().is_ok());
+}
+
+#[test]
+fn test_auto_size() {
+    let config = ControllerConfig::auto(200);
+    assert_eq!((config.lfu_cache_size, config.lru_cache_size, config.gds_cache_size), (200, 200, 0));
+    assert!((config.discount_rate - ControllerConfig::new(200, 200, 200).discount_rate).abs() < 1e-12);
+    assert!(config.warnings().is_empty());
+
+    // Configured sizes are ignored once derived
+    let mut config = ControllerConfig::new(100, 3, 3);
+    config.gds_cache_size = 1;
+    assert_eq!(config.warnings().len(), 3);
+    config.auto_size = true;
+    assert!(config.warnings().is_empty());
+    assert_eq!(config.derived().gds_cache_size, 100);
+    assert!(Controller::with_config(config).unwrap().warnings().is_empty());
+    assert_eq!(Controller::new(200, 20, 20).unwrap().warnings().len(), 2);
+
+    let mut controller = Controller::with_cache_size(4).unwrap();
+    for key in 0..12u32 {
+        controller.insert(&key.to_string(), key);
+    }
+    assert!(controller.lfu_history().count() <= 4 && controller.lru_history().count() <= 4);
+    assert!(controller.lfu_history().count() + controller.lru_history().count() >= 4);
+    assert!(matches!(Controller::with_cache_size(0), Err(LecarError::InvalidConfig(_))));
+}
+
+#[test]
+fn test_clear_drain_reset() {
+    let fill = |controller: &mut Controller| {
+        for key in 0..8u32 {
+            controller.insert(&key.to_string(), key);
+        }
+        for key in 0..8u32 {
+            controller.get(&key.to_string());
+        }
+    };
+
+    let mut controller = Controller::new(4, 4, 4).unwrap();
+    fill(&mut controller);
+    let weights = controller.lfu_prob();
+    assert_ne!(weights, 0.5);
+
+    let removals = Arc::new(Mutex::new(Vec::new()));
+    let listened = Arc::clone(&removals);
+    controller.set_eviction_listener(Some(Box::new(move |key, value, location, removal| {
+        listened.lock().unwrap().push((key.to_string(), value, location, removal));
+    })));
+
+    let drained: Vec<(String, u32, Location)> = controller.drain().collect();
+    assert_eq!(drained.len(), 8);
+    assert_eq!(drained.iter().filter(|(_, _, location)| *location == Location::Main).count(), 4);
+    assert!(drained.iter().all(|(key, value, _)| key == &value.to_string()));
+    assert_eq!(controller.counter.drained, 8);
+    assert!(ICache::is_empty(&controller));
+    assert_eq!(controller.peek("7", true), None);
+    assert_eq!(controller.lfu_prob(), weights);
+    let reported: Vec<(String, u32, Location)> = removals.lock().unwrap().drain(..)
+        .map(|(key, value, location, removal)| {
+            assert_eq!(removal, Removal::Drained);
+            (key, value, location)
+        })
+        .collect();
+    assert_eq!(reported, drained);
+
+    // Ejections from the main cache are reported as evictions
+    fill(&mut controller);
+    let evicted = removals.lock().unwrap().drain(..).filter(|(_, _, location, removal)| (*location, *removal) == (Location::Main, Removal::Evicted)).count();
+    assert!(evicted >= 4, "{} evictions reported", evicted);
+
+    controller.clear();
+    assert!(ICache::is_empty(&controller));
+    assert_eq!(controller.lfu_history().count() + controller.lru_history().count(), 0);
+    assert_eq!(controller.counter.cleared, 8);
+    assert_eq!(removals.lock().unwrap().len(), 8);
+    assert!(removals.lock().unwrap().drain(..).all(|(_, _, _, removal)| removal == Removal::Cleared));
+
+    controller.insert("a", 1);
+    assert_eq!(controller.remove("a"), Some((1, Location::Main)));
+    assert_eq!(*removals.lock().unwrap(), vec![("a".to_string(), 1, Location::Main, Removal::Removed)]);
+    controller.set_eviction_listener(None);
+
+    fill(&mut controller);
+    controller.reset_learner();
+    assert_eq!(controller.lfu_prob(), 0.5);
+    assert_eq!(ICache::len(&controller), 4);
+    assert_eq!(controller.counter.learner_resets, 1);
+    assert!(controller.check_invariants().is_ok());
+}
+
+#[test]
+fn test_weights() {
+    let mut controller = Controller::new(4, 4, 4).unwrap();
+    assert_eq!(controller.weights(), Weights { lfu: 0.5, lru: 0.5, gds: 0.0 });
+
+    let skewed = Weights { lfu: 0.75, lru: 0.25, gds: 0.0 };
+    assert!(controller.set_weights(skewed).is_ok());
+    controller.freeze_weights(true);
+    for key in 0..12u32 {
+        controller.insert(&key.to_string(), key);
+    }
+    for key in 0..12u32 {
+        controller.get(&key.to_string());
+    }
+    assert_eq!(controller.weights(), skewed);
+
+    assert!(matches!(controller.set_weights(Weights { lfu: 0.5, lru: 0.6, gds: 0.0 }), Err(LecarError::InvalidConfig(_))));
+    assert!(matches!(controller.set_weights(Weights { lfu: 0.4, lru: 0.3, gds: 0.3 }), Err(LecarError::InvalidConfig(_))));
+
+    // Weights within the tolerance are normalized before being used
+    assert!(controller.set_weights(Weights { lfu: 0.5000004, lru: 0.5, gds: 0.0 }).is_ok());
+    let weights = controller.weights();
+    assert_eq!(weights.lfu + weights.lru + weights.gds, 1.0);
+    assert!(controller.check_invariants().is_ok());
+
+    // Warm start from the weights of a previous run, saved to a file no other test run uses
+    let nanos = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_nanos();
+    let path = std::env::temp_dir().join(format!("lecar_weights_{}_{}.json", std::process::id(), nanos));
+    let path = path.to_str().unwrap();
+    skewed.save(path).unwrap();
+    let mut config = ControllerConfig::new(4, 4, 4);
+    config.weights = Some(Weights::load(path).unwrap());
+    std::fs::remove_file(path).unwrap();
+    let controller = Controller::with_config(config).unwrap();
+    assert_eq!(controller.weights(), skewed);
+    assert!(controller.check_invariants().is_ok());
+}
+
+/// Runs a scan heavy workload, which LFU wins, then a shifting hot set, which LRU wins
+/// Returns the LFU weight after each phase
+fn replay_workload_shift(controller: &mut Controller) -> (f64, f64) {
+    let phases = [
+        Workload::ScanHeavy { keys: 500, exponent: 1.0, scan_every: 100, scan_length: 100 },
+        Workload::ShiftingHotset { keys: 5_000, hot_keys: 40, hot_probability: 0.95, shift_every: 400 }
+    ];
+
+    let mut weights = phases.iter().enumerate().map(|(seed, workload)| {
+        for access in workload.generate(20_000, seed as u64) {
+            if controller.get(&access.key).is_none() {
+                controller.insert(&access.key, 0);
+            }
+        }
+        controller.weights().lfu
+    });
+
+    (weights.next().unwrap(), weights.next().unwrap())
+}
+
+#[test]
+fn test_exploration() {
+    // Rounded weights collapse and never recover
+    let mut config = ControllerConfig::new(50, 50, 50);
+    let collapsed = replay_workload_shift(&mut Controller::with_config(config.clone()).unwrap());
+    assert_eq!(collapsed, (1.0, 1.0));
+
+    config.exploration = 0.05;
+    config.renormalize_every = 100;
+    let mut controller = Controller::with_config(config.clone()).unwrap();
+    let (after_scans, after_shift) = replay_workload_shift(&mut controller);
+    assert!(after_scans > 0.9 && after_scans <= 0.95, "LFU weight of {} after the scans", after_scans);
+    assert!(after_shift < 0.5, "LFU weight of {} after the shift", after_shift);
+    assert!(controller.check_invariants().is_ok());
+
+    config.exploration = 0.6;
+    assert!(matches!(Controller::with_config(config.clone()), Err(LecarError::InvalidConfig(_))));
+
+    // Full precision keeps the small rewards rounding discards
+    config.exploration = 0.0;
+    config.full_precision = true;
+    let mut controller = Controller::with_config(config).unwrap();
+    replay_workload_shift(&mut controller);
+    assert!(((controller.weights().lfu * 100.0).round() - controller.weights().lfu * 100.0).abs() > 1e-9);
+
+    let floored = Weights { lfu: 0.99, lru: 0.01, gds: 0.0 }.floored(0.1, false);
+    assert!((floored.lfu - 0.9).abs() < 1e-12 && (floored.lru - 0.1).abs() < 1e-12);
+    let floored = Weights { lfu: 0.0, lru: 0.0, gds: 1.0 }.floored(0.2, true);
+    assert!((floored.lfu - 0.2).abs() < 1e-12 && (floored.gds - 0.6).abs() < 1e-12);
+    let normalized = Weights { lfu: 0.67, lru: -0.01, gds: 0.34 }.normalized();
+    assert_eq!(normalized.lru, 0.0);
+    assert!((normalized.lfu + normalized.gds - 1.0).abs() < 1e-12);
+}
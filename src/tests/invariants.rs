@@ -0,0 +1,68 @@
+use crate::cache::ICache;
+use crate::controller::Controller;
+use proptest::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Clone, Debug)]
+enum Operation {
+    Get(u8),
+    Insert(u8, u32)
+}
+
+fn operation() -> impl Strategy<Value = Operation> {
+    prop_oneof![
+        (0u8..32).prop_map(Operation::Get),
+        (0u8..32, any::<u32>()).prop_map(|(key, value)| Operation::Insert(key, value))
+    ]
+}
+
+proptest! {
+    #[test]
+    fn test_controller_invariants(
+        cache_size in 1usize..12,
+        lfu_cache_size in 0usize..6,
+        lru_cache_size in 0usize..6,
+        operations in prop::collection::vec(operation(), 1..300)
+    ) {
+        let mut controller = Controller::new(cache_size, lfu_cache_size, lru_cache_size).unwrap();
+        // Reference model: the last value written for every key ever inserted
+        let mut model: HashMap<String, u32> = HashMap::new();
+
+        for operation in operations {
+            match operation {
+                Operation::Get(key) => {
+                    let key = key.to_string();
+                    match controller.get(&key) {
+                        Some(value) => {
+                            prop_assert_eq!(Some(&value), model.get(&key));
+                            prop_assert!(ICache::contains(&controller, &key));
+                        },
+                        None => prop_assert!(!ICache::contains(&controller, &key))
+                    }
+                },
+                Operation::Insert(key, value) => {
+                    let key = key.to_string();
+                    controller.insert(&key, value);
+                    model.insert(key.clone(), value);
+                    prop_assert!(ICache::contains(&controller, &key));
+                }
+            }
+
+            if let Err(violation) = controller.check_invariants() {
+                return Err(TestCaseError::fail(violation));
+            }
+
+            let (main, lfu, lru) = controller.len();
+            prop_assert_eq!(main, model.len().min(cache_size));
+            prop_assert!(main + lfu + lru <= model.len());
+        }
+
+        // Every key the controller still knows about returns its last written value
+        let known: HashSet<String> = model.keys().cloned().collect();
+        for key in known {
+            if let Some(value) = controller.get(&key) {
+                prop_assert_eq!(value, model[&key]);
+            }
+        }
+    }
+}
@@ -1,7 +1,16 @@
+mod baselines;
+mod conformance;
+mod controller;
+mod invariants;
+mod snapshot;
+mod trace;
+mod workload;
+
 use crate::controller::Controller;
 use rand::prelude::*;
 
 /// Represents how the cache is being accessed
+#[allow(clippy::upper_case_acronyms)]
 pub enum AccessMethod {
     READ,
     WRITE
@@ -22,12 +31,12 @@ fn test_controller() {
     const LFU_CACHE_SIZE: usize = 20;
     const LRU_CACHE_SIZE: usize = 20;
 
-    let mut cache_controller = Controller::new(CACHE_SIZE, LFU_CACHE_SIZE, LRU_CACHE_SIZE);
+    let mut cache_controller = Controller::new(CACHE_SIZE, LFU_CACHE_SIZE, LRU_CACHE_SIZE).unwrap();
 
     let mut rng = rand::thread_rng();
 
-    for _ in 0..rng.gen_range(1_000, 2_000) {
-        let key = rng.gen_range(0, 300);
+    for _ in 0..rng.gen_range(1_000..2_000) {
+        let key = rng.gen_range(0..300);
         let key = key.to_string();
 
         match AccessMethod::from_bool(rng.gen_bool(0.10)) {
@@ -35,8 +44,8 @@ fn test_controller() {
                 cache_controller.get(&key);
             },
             AccessMethod::WRITE => {
-                let data = "abcdefg";
-                cache_controller.insert(&key, data.to_string());
+                let data: u32 = 0xabcdef;
+                cache_controller.insert(&key, data);
             }
         }
     }
@@ -0,0 +1,147 @@
+use crate::controller::{Controller, ControllerConfig};
+use crate::error::LecarError;
+use crate::sketch::CountMinSketch;
+
+#[test]
+fn test_snapshot_round_trip() {
+    let mut controller = Controller::new(4, 2, 2).unwrap();
+    for key in 0..10u32 {
+        controller.insert(&key.to_string(), key);
+    }
+
+    let mut restored = Controller::from_snapshot(&controller.to_snapshot().unwrap()).unwrap();
+    assert_eq!(restored.len(), controller.len());
+    assert_eq!(restored.get("9"), Some(9));
+}
+
+#[test]
+fn test_snapshot_rejects_untrusted_input() {
+    assert!(matches!(Controller::from_snapshot(b"{\"cache\""), Err(LecarError::Encoding(_))));
+
+    // A main cache holding more items than its capacity
+    let mut controller = Controller::new(2, 1, 1).unwrap();
+    controller.insert("a", 1);
+    controller.insert("b", 2);
+    let snapshot = String::from_utf8(controller.to_snapshot().unwrap()).unwrap();
+    let oversized = snapshot.replacen("\"capacity\":2", "\"capacity\":1", 1);
+    assert!(matches!(Controller::from_snapshot(oversized.as_bytes()), Err(LecarError::InvalidSnapshot(_))));
+
+    // Extreme times are rejected or restored depending on what the platform's Instant can represent
+    let mut timestamps = snapshot.clone();
+    let start = timestamps.find("\"last_used\":").unwrap() + "\"last_used\":".len();
+    let end = start + timestamps[start..].find(',').unwrap();
+    for millis in ["0", "18446744073709551615"] {
+        timestamps.replace_range(start..end, millis);
+        if let Ok(restored) = Controller::from_snapshot(timestamps.as_bytes()) {
+            let _ = restored.to_snapshot();
+        }
+        timestamps = snapshot.clone();
+    }
+}
+
+#[test]
+fn test_invalid_config() {
+    assert!(matches!(Controller::new(0, 1, 1), Err(LecarError::InvalidConfig(_))));
+    assert!(Controller::new(1, 0, 0).is_ok());
+
+    let mut config = ControllerConfig::new(4, 2, 2);
+    config.discount_rate = 0.0;
+    assert!(matches!(Controller::with_config(config.clone()), Err(LecarError::InvalidConfig(_))));
+    config.discount_rate = 0.9;
+    config.learning_rate = f64::NAN;
+    assert!(matches!(Controller::with_config(config.clone()), Err(LecarError::InvalidConfig(_))));
+    config.learning_rate = 1e300;
+    assert!(matches!(Controller::with_config(config), Err(LecarError::InvalidConfig(_))));
+}
+
+#[test]
+fn test_snapshot_rejects_invalid_settings() {
+    let snapshot: serde_json::Value = serde_json::from_slice(&Controller::new(4, 2, 2).unwrap().to_snapshot().unwrap()).unwrap();
+
+    let settings: [(&str, serde_json::Value); 6] = [
+        ("learning_rate", serde_json::json!(-1.0)),
+        ("learning_rate", serde_json::json!(1e300)),
+        ("discount_rate", serde_json::json!(1.5)),
+        ("discount_rate", serde_json::json!(0.0)),
+        ("exploration", serde_json::json!(0.9)),
+        ("frequency_aging", serde_json::json!({ "Halving": { "period": 0 } }))
+    ];
+    for (field, value) in settings.iter() {
+        let mut invalid = snapshot.clone();
+        invalid[*field] = value.clone();
+        let bytes = serde_json::to_vec(&invalid).unwrap();
+        assert!(matches!(Controller::from_snapshot(&bytes), Err(LecarError::InvalidSnapshot(_))), "{} of {} was restored", field, value);
+    }
+}
+
+#[test]
+fn test_zero_capacity() {
+    // A snapshot cannot bring back a main cache without capacity
+    let snapshot = String::from_utf8(Controller::new(1, 1, 1).unwrap().to_snapshot().unwrap()).unwrap();
+    let zero = snapshot.replacen("\"capacity\":1", "\"capacity\":0", 1);
+    assert!(matches!(Controller::from_snapshot(zero.as_bytes()), Err(LecarError::InvalidSnapshot(_))));
+}
+
+#[test]
+fn test_snapshot_rejects_corrupt_admission() {
+    let mut config = ControllerConfig::new(4, 2, 2);
+    config.admission = true;
+    let controller = Controller::with_config(config).unwrap();
+    let snapshot: serde_json::Value = serde_json::from_slice(&controller.to_snapshot().unwrap()).unwrap();
+
+    let corruptions: [(&str, serde_json::Value); 4] = [
+        ("/admission/sketch/rows", serde_json::json!([[0]])),
+        ("/admission/sketch/mask", serde_json::json!(2)),
+        ("/admission/sketch/rows/0/0", serde_json::json!(16)),
+        ("/admission/doorkeeper/mask", serde_json::json!(127))
+    ];
+    for (pointer, value) in corruptions.iter() {
+        let mut corrupt = snapshot.clone();
+        *corrupt.pointer_mut(pointer).unwrap() = value.clone();
+        let bytes = serde_json::to_vec(&corrupt).unwrap();
+        assert!(matches!(Controller::from_snapshot(&bytes), Err(LecarError::InvalidSnapshot(_))), "{} was restored", pointer);
+    }
+}
+
+#[test]
+fn test_snapshot_with_exhausted_counters() {
+    let mut config = ControllerConfig::new(2, 1, 1);
+    config.admission = true;
+    config.renormalize_every = 1;
+    let mut controller = Controller::with_config(config).unwrap();
+    controller.insert("a", 1);
+    controller.insert("b", 2);
+    let mut snapshot: serde_json::Value = serde_json::from_slice(&controller.to_snapshot().unwrap()).unwrap();
+
+    // Every counter a request increments is at its largest value
+    let exhausted = serde_json::json!(u64::MAX);
+    for pointer in [
+        "/clock", "/weight_updates", "/cache/sequence", "/cache/cache/a/frequency", "/cache/cache/a/recent",
+        "/counter/num_queries", "/counter/hits", "/counter/misses", "/counter/hit_bytes", "/counter/missed_bytes",
+        "/counter/admitted", "/counter/rejected", "/admission/additions", "/admission/sketch/additions"
+    ] {
+        *snapshot.pointer_mut(pointer).unwrap_or_else(|| panic!("no {} in the snapshot", pointer)) = exhausted.clone();
+    }
+
+    let mut restored = Controller::from_snapshot(&serde_json::to_vec(&snapshot).unwrap()).unwrap();
+    assert_eq!(restored.get("a"), Some(1));
+    assert_eq!(restored.get("c"), None);
+    restored.insert("c", 3);
+    restored.insert("d", 4);
+    restored.insert("a", 5);
+    restored.get("b");
+    assert!(restored.check_invariants().is_ok());
+}
+
+#[test]
+fn test_sketch_hash_is_stable() {
+    // Sketches in snapshots stay valid across toolchains only if keys always hash to the same counters
+    let mut sketch = CountMinSketch::new(64);
+    sketch.increment("lecar");
+    let sketch = serde_json::to_value(&sketch).unwrap();
+    let counters: Vec<usize> = sketch["rows"].as_array().unwrap()
+        .iter()
+        .map(|row| row.as_array().unwrap().iter().position(|counter| counter == 1).unwrap())
+        .collect();
+    assert_eq!(counters, vec![56, 16, 11, 8]);
+}
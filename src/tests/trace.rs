@@ -0,0 +1,72 @@
+use crate::controller::Controller;
+use crate::error::LecarError;
+use crate::trace::{Access, Op, TraceFormat, TraceReader};
+
+fn read(input: &str, format: TraceFormat) -> Vec<Access> {
+    TraceReader::new(input.as_bytes(), format)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap()
+}
+
+#[test]
+fn test_trace_formats() {
+    let plain = read("a\n\n# comment\nb\na\n", TraceFormat::Plain);
+    assert_eq!(plain.iter().map(|a| a.key.as_str()).collect::<Vec<_>>(), vec!["a", "b", "a"]);
+    assert!(plain.iter().all(|a| a.op == Op::Get));
+
+    let csv = read("op,key,size\nget,a,10\nset,b,20,5\ndelete,a,0\n", TraceFormat::Csv);
+    assert_eq!(csv.len(), 3);
+    assert_eq!(csv[1], Access { key: "b".to_string(), op: Op::Set, size: 20, timestamp: 5 });
+    assert_eq!(csv[2].op, Op::Delete);
+
+    let arc = read("100 3 0 1\n7 1 0 2\n", TraceFormat::Arc);
+    assert_eq!(arc.iter().map(|a| a.key.as_str()).collect::<Vec<_>>(), vec!["100", "101", "102", "7"]);
+
+    let spc = read("0,20941264,8192,W,0.551706\n", TraceFormat::Spc);
+    assert_eq!(spc[0], Access { key: "0:20941264".to_string(), op: Op::Set, size: 8192, timestamp: 551_706 });
+
+    let msr = read("128166372003061629,hm,1,Read,7014609920,24576,41286\n", TraceFormat::Msr);
+    assert_eq!(msr[0], Access { key: "hm:1:7014609920".to_string(), op: Op::Get, size: 24576, timestamp: 12_816_637_200_306_162 });
+
+    let twitter = read("0,q:key,9,120,22,gets,0\n1,q:key,9,130,22,set,3600\n", TraceFormat::Twitter);
+    assert_eq!(twitter[0], Access { key: "q:key".to_string(), op: Op::Get, size: 129, timestamp: 0 });
+    assert_eq!(twitter[1].op, Op::Set);
+    assert_eq!(twitter[1].timestamp, 1_000_000);
+}
+
+#[test]
+fn test_trace_errors() {
+    let mut reader = TraceReader::new("get,a,10\nget,b,ten\n".as_bytes(), TraceFormat::Csv);
+    assert!(reader.next().unwrap().is_ok());
+    match reader.next().unwrap() {
+        Err(LecarError::Parse { line, .. }) => assert_eq!(line, 2),
+        other => panic!("expected a parse error, got {:?}", other)
+    }
+
+    // A single record cannot make the reader buffer an unbounded number of blocks
+    let mut reader = TraceReader::new("0 18446744073709551615 0 1\n".as_bytes(), TraceFormat::Arc);
+    assert!(matches!(reader.next(), Some(Err(LecarError::Parse { line: 1, .. }))));
+
+    let mut reader = TraceReader::new("18446744073709551615,k,1,1,0,get,0\n".as_bytes(), TraceFormat::Twitter);
+    assert!(matches!(reader.next(), Some(Err(LecarError::Parse { line: 1, .. }))));
+}
+
+#[test]
+fn test_trace_replay() {
+    let mut controller = Controller::new(2, 2, 2).unwrap();
+    let accesses = read("set,a,1\nset,b,2\nget,a,0\nget,c,0\ndelete,a,0\nget,a,0\n", TraceFormat::Csv);
+    let values: Vec<Option<u32>> = accesses.iter().map(|a| a.apply(&mut controller)).collect();
+
+    assert_eq!(values, vec![None, None, Some(1), None, None, None]);
+}
+
+#[test]
+fn test_trace_replay_bytes() {
+    let mut controller = Controller::new(2, 2, 2).unwrap();
+    let accesses = read("set,a,100\nset,b,300\nget,a,0\nget,c,0\nset,c,50\nget,c,0\n", TraceFormat::Csv);
+    accesses.iter().for_each(|a| { a.apply(&mut controller); });
+
+    // The sizes of the records reach the byte hit ratio, hits on a and c and the miss on c
+    assert_eq!((controller.counter.hit_bytes, controller.counter.missed_bytes), (150, 50));
+    assert_eq!(controller.counter.byte_hit_ratio(), 0.75);
+}
@@ -0,0 +1,84 @@
+use crate::controller::Controller;
+use crate::workload::Workload;
+use std::collections::HashMap;
+
+fn counts(workload: &Workload, length: usize) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for access in workload.generate(length, 7) {
+        *counts.entry(access.key).or_insert(0) += 1;
+    }
+
+    counts
+}
+
+#[test]
+fn test_workload_seeded() {
+    let workload = Workload::Zipf { keys: 100, exponent: 1.0 };
+
+    assert_eq!(workload.generate(500, 1), workload.generate(500, 1));
+    assert_ne!(workload.generate(500, 1), workload.generate(500, 2));
+}
+
+#[test]
+fn test_workload_shapes() {
+    let zipf = counts(&Workload::Zipf { keys: 100, exponent: 1.2 }, 10_000);
+    assert!(zipf["0"] > zipf["1"]);
+    assert!(zipf["1"] > zipf.get("50").copied().unwrap_or_default());
+
+    let looping: Vec<String> = Workload::Loop { keys: 3 }.generate(7, 0).into_iter().map(|a| a.key).collect();
+    assert_eq!(looping, vec!["0", "1", "2", "0", "1", "2", "0"]);
+
+    let scans = counts(&Workload::ScanHeavy { keys: 10, exponent: 1.0, scan_every: 50, scan_length: 50 }, 1_000);
+    let one_hit_wonders = scans.iter().filter(|(key, _)| key.starts_with("scan:")).count();
+    assert_eq!(one_hit_wonders, 500);
+    assert!(scans.iter().filter(|(key, _)| key.starts_with("scan:")).all(|(_, count)| *count == 1));
+
+    let shifting = Workload::ShiftingHotset { keys: 1_000, hot_keys: 10, hot_probability: 1.0, shift_every: 100 };
+    let accesses = shifting.generate(200, 0);
+    assert!(accesses[..100].iter().all(|a| a.key.parse::<usize>().unwrap() < 10));
+    assert!(accesses[100..].iter().all(|a| (10..20).contains(&a.key.parse::<usize>().unwrap())));
+}
+
+#[test]
+fn test_workload_phases() {
+    let workload = Workload::Phases(vec![
+        (Workload::Loop { keys: 2 }, 4),
+        (Workload::ShiftingHotset { keys: 100, hot_keys: 5, hot_probability: 1.0, shift_every: 1_000 }, 2)
+    ]);
+    let keys: Vec<String> = workload.generate(8, 3).into_iter().map(|a| a.key).collect();
+
+    assert_eq!(&keys[..4], &["0", "1", "0", "1"]);
+    assert!(keys[4..6].iter().all(|key| key.parse::<usize>().unwrap() < 5));
+    assert_eq!(&keys[6..], &["0", "1"]);
+}
+
+#[test]
+fn test_controller_phases() {
+    const CACHE_SIZE: usize = 50;
+    const PHASE_LENGTH: usize = 2_000;
+
+    // A shifting hot set favours LRU, then a skewed popularity favours LFU
+    let workload = Workload::Phases(vec![
+        (Workload::ShiftingHotset { keys: 5_000, hot_keys: 30, hot_probability: 0.9, shift_every: 300 }, PHASE_LENGTH),
+        (Workload::Zipf { keys: 500, exponent: 1.0 }, PHASE_LENGTH)
+    ]);
+    let mut controller = Controller::new(CACHE_SIZE, CACHE_SIZE, CACHE_SIZE).unwrap();
+
+    let mut weights = Vec::new();
+    for (index, access) in workload.generate(2 * PHASE_LENGTH, 11).iter().enumerate() {
+        if controller.get(&access.key).is_none() {
+            controller.insert(&access.key, access.size);
+        }
+        if (index + 1) % PHASE_LENGTH == 0 {
+            weights.push(controller.lfu_prob());
+        }
+    }
+
+    // The LFU weight falls during the first phase and rises during the second
+    assert!(weights[0] < 0.5, "LFU weight of {} after the shifting hot set", weights[0]);
+    assert!(weights[1] > 0.5, "LFU weight of {} after the skewed phase", weights[1]);
+
+    let lengths = controller.len();
+    assert_eq!(lengths.0, CACHE_SIZE);
+    assert!(lengths.1 + lengths.2 > 0);
+}
@@ -0,0 +1,255 @@
+use crate::controller::{Controller, InsertOptions};
+use crate::error::LecarError;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Lines};
+use std::path::Path;
+
+/// Size in bytes of a block in the ARC traces
+const ARC_BLOCK_SIZE: u32 = 512;
+
+/// Largest number of blocks a single ARC record may span, larger records are rejected rather than buffered
+const ARC_MAX_BLOCKS: u64 = 1 << 16;
+
+/// Operation performed by a single access
+#[derive(Serialize, Deserialize, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Op {
+    Get,
+    Set,
+    Delete
+}
+
+impl Op {
+    /// Parses the operation names used across the supported trace formats
+    fn parse(op: &str) -> Option<Self> {
+        match op.trim().to_ascii_lowercase().as_str() {
+            "get" | "gets" | "r" | "read" => Some(Self::Get),
+            "set" | "add" | "replace" | "cas" | "append" | "prepend" | "incr" | "decr"
+            | "put" | "insert" | "w" | "write" => Some(Self::Set),
+            "delete" | "del" | "remove" => Some(Self::Delete),
+            _ => None
+        }
+    }
+}
+
+/// A single request read from a trace
+/// The timestamp is in microseconds for formats that record one
+/// Otherwise it is the position of the access in the trace
+#[derive(Serialize, Deserialize, Clone, Eq, PartialEq, Debug)]
+pub struct Access {
+    pub key: String,
+    pub op: Op,
+    pub size: u32,
+    pub timestamp: u64
+}
+
+impl Access {
+    /// Replays the access against the controller
+    /// Reads go through `Controller::get`, writes through `Controller::insert_with_options` with the size as value and object size
+    /// And deletes through `Controller::remove`
+    /// Returns the value for reads that hit
+    pub fn apply(&self, controller: &mut Controller) -> Option<u32> {
+        match self.op {
+            Op::Get => controller.get(&self.key),
+            Op::Set => {
+                controller.insert_with_options(&self.key, self.size, InsertOptions { size: Some(self.size), ..Default::default() });
+                None
+            },
+            Op::Delete => {
+                controller.remove(&self.key);
+                None
+            }
+        }
+    }
+}
+
+/// Supported trace formats
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum TraceFormat {
+    /// One key per line, every access is a read
+    Plain,
+    /// `op,key,size[,timestamp]` with an optional header
+    Csv,
+    /// ARC traces: `start_block num_blocks ignored request_number`, one access per block
+    Arc,
+    /// UMass SPC traces: `asu,lba,size,opcode,timestamp`
+    Spc,
+    /// MSR Cambridge traces: `timestamp,hostname,disk,type,offset,size,response_time`
+    Msr,
+    /// Twitter cache traces: `timestamp,key,key_size,value_size,client_id,operation,ttl`
+    Twitter
+}
+
+/// Streaming reader turning trace records into accesses
+pub struct TraceReader<R> {
+    lines: Lines<R>,
+    format: TraceFormat,
+    line: usize,
+    pending: VecDeque<Access>
+}
+
+/// Opens a trace file of the given format
+pub fn open<P: AsRef<Path>>(path: P, format: TraceFormat) -> Result<TraceReader<BufReader<File>>, LecarError> {
+    let file = File::open(path)?;
+
+    Ok(TraceReader::new(BufReader::new(file), format))
+}
+
+impl<R: BufRead> TraceReader<R> {
+    /// Instantiates a new reader over the given source
+    pub fn new(reader: R, format: TraceFormat) -> Self {
+        Self {
+            lines: reader.lines(),
+            format,
+            line: 0,
+            pending: VecDeque::new()
+        }
+    }
+
+    /// Builds a parse error for the current line
+    fn error(&self, message: String) -> LecarError {
+        LecarError::Parse { line: self.line, message }
+    }
+
+    /// Retrieves the field at the given column
+    fn field<'a>(&self, fields: &[&'a str], index: usize, name: &str) -> Result<&'a str, LecarError> {
+        fields
+            .get(index)
+            .map(|field| field.trim())
+            .ok_or_else(|| self.error(format!("missing {} column", name)))
+    }
+
+    /// Parses the field at the given column
+    fn parse_field<T: std::str::FromStr>(&self, fields: &[&str], index: usize, name: &str) -> Result<T, LecarError> {
+        let field = self.field(fields, index, name)?;
+
+        field
+            .parse()
+            .map_err(|_| self.error(format!("invalid {} `{}`", name, field)))
+    }
+
+    /// Parses the operation at the given column
+    fn parse_op(&self, fields: &[&str], index: usize) -> Result<Op, LecarError> {
+        let field = self.field(fields, index, "operation")?;
+
+        Op::parse(field).ok_or_else(|| self.error(format!("unknown operation `{}`", field)))
+    }
+
+    /// Parses a single record, pushing its accesses onto the pending queue
+    fn parse_line(&mut self, line: &str) -> Result<(), LecarError> {
+        let position = self.line as u64;
+
+        match self.format {
+            TraceFormat::Plain => {
+                let key = line.split_whitespace().next().unwrap_or_default();
+                self.pending.push_back(Access { key: key.to_string(), op: Op::Get, size: 1, timestamp: position });
+            },
+            TraceFormat::Csv => {
+                let fields: Vec<&str> = line.split(',').collect();
+                // Skip the optional header
+                if self.line == 1 && Op::parse(fields[0]).is_none() {
+                    return Ok(());
+                }
+
+                let op = self.parse_op(&fields, 0)?;
+                let key = self.field(&fields, 1, "key")?.to_string();
+                let size = self.parse_field(&fields, 2, "size")?;
+                let timestamp = match fields.get(3) {
+                    Some(_) => self.parse_field(&fields, 3, "timestamp")?,
+                    None => position
+                };
+                self.pending.push_back(Access { key, op, size, timestamp });
+            },
+            TraceFormat::Arc => {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                let start: u64 = self.parse_field(&fields, 0, "start block")?;
+                let blocks: u64 = self.parse_field(&fields, 1, "block count")?;
+                let request: u64 = self.parse_field(&fields, 3, "request number")?;
+                if blocks > ARC_MAX_BLOCKS {
+                    return Err(self.error(format!("block count {} is over the limit of {}", blocks, ARC_MAX_BLOCKS)));
+                }
+
+                for block in start..start.saturating_add(blocks) {
+                    self.pending.push_back(Access { key: block.to_string(), op: Op::Get, size: ARC_BLOCK_SIZE, timestamp: request });
+                }
+            },
+            TraceFormat::Spc => {
+                let fields: Vec<&str> = line.split(',').collect();
+                let asu = self.field(&fields, 0, "asu")?;
+                let lba = self.field(&fields, 1, "lba")?;
+                let size = self.parse_field(&fields, 2, "size")?;
+                let op = self.parse_op(&fields, 3)?;
+                let seconds: f64 = self.parse_field(&fields, 4, "timestamp")?;
+                self.pending.push_back(Access {
+                    key: format!("{}:{}", asu, lba),
+                    op,
+                    size,
+                    timestamp: (seconds * 1_000_000.0) as u64
+                });
+            },
+            TraceFormat::Msr => {
+                let fields: Vec<&str> = line.split(',').collect();
+                // Timestamps are Windows filetimes in 100ns ticks
+                let ticks: u64 = self.parse_field(&fields, 0, "timestamp")?;
+                let host = self.field(&fields, 1, "hostname")?;
+                let disk = self.field(&fields, 2, "disk number")?;
+                let op = self.parse_op(&fields, 3)?;
+                let offset = self.field(&fields, 4, "offset")?;
+                let size = self.parse_field(&fields, 5, "size")?;
+                self.pending.push_back(Access {
+                    key: format!("{}:{}:{}", host, disk, offset),
+                    op,
+                    size,
+                    timestamp: ticks / 10
+                });
+            },
+            TraceFormat::Twitter => {
+                let fields: Vec<&str> = line.split(',').collect();
+                let seconds: u64 = self.parse_field(&fields, 0, "timestamp")?;
+                let key = self.field(&fields, 1, "key")?.to_string();
+                let key_size: u32 = self.parse_field(&fields, 2, "key size")?;
+                let value_size: u32 = self.parse_field(&fields, 3, "value size")?;
+                let op = self.parse_op(&fields, 5)?;
+                let timestamp = seconds
+                    .checked_mul(1_000_000)
+                    .ok_or_else(|| self.error(format!("timestamp {} overflows in microseconds", seconds)))?;
+                self.pending.push_back(Access {
+                    key,
+                    op,
+                    size: key_size.saturating_add(value_size),
+                    timestamp
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<R: BufRead> Iterator for TraceReader<R> {
+    type Item = Result<Access, LecarError>;
+
+    /// Yields the next access, reading more records as needed
+    /// Blank lines and lines starting with `#` are skipped
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pending.is_empty() {
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(err) => return Some(Err(err.into()))
+            };
+            self.line += 1;
+
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+
+            if let Err(err) = self.parse_line(trimmed) {
+                return Some(Err(err));
+            }
+        }
+
+        self.pending.pop_front().map(Ok)
+    }
+}
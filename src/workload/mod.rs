@@ -0,0 +1,174 @@
+use crate::trace::{Access, Op};
+use rand::Rng;
+use rand_chacha::rand_core::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+/// Description of a synthetic workload
+/// Keys are numbered from 0, scans use fresh keys that are never requested again
+#[derive(Clone, Debug)]
+pub enum Workload {
+    /// Every key in 0..keys is equally likely
+    Uniform { keys: usize },
+    /// Key popularity follows a Zipf distribution with the given exponent, key 0 being the most popular
+    Zipf { keys: usize, exponent: f64 },
+    /// Zipfian requests interrupted every `scan_every` requests by a scan of `scan_length` one-hit keys
+    ScanHeavy { keys: usize, exponent: f64, scan_every: usize, scan_length: usize },
+    /// Cycles through 0..keys in order
+    Loop { keys: usize },
+    /// A window of `hot_keys` receives `hot_probability` of the requests and moves every `shift_every` requests
+    ShiftingHotset { keys: usize, hot_keys: usize, hot_probability: f64, shift_every: usize },
+    /// Runs each workload for the given number of requests, cycling back to the first phase at the end
+    Phases(Vec<(Workload, usize)>)
+}
+
+impl Workload {
+    /// Instantiates a generator for the workload seeded with the given seed
+    pub fn generator(&self, seed: u64) -> WorkloadGenerator {
+        WorkloadGenerator {
+            rng: ChaCha8Rng::seed_from_u64(seed),
+            source: Source::new(self),
+            position: 0,
+            scanned: 0
+        }
+    }
+
+    /// Generates the given number of accesses seeded with the given seed
+    pub fn generate(&self, length: usize, seed: u64) -> Vec<Access> {
+        self.generator(seed).take(length).collect()
+    }
+}
+
+/// Inverse transform sampler over a Zipf distribution
+#[derive(Clone, Debug)]
+struct Zipf {
+    cdf: Vec<f64>
+}
+
+impl Zipf {
+    /// Precomputes the cumulative distribution for the given number of keys
+    fn new(keys: usize, exponent: f64) -> Self {
+        let mut total = 0.0;
+        let mut cdf: Vec<f64> = (1..=keys.max(1))
+            .map(|rank| {
+                total += 1.0 / (rank as f64).powf(exponent);
+                total
+            })
+            .collect();
+        cdf.iter_mut().for_each(|p| *p /= total);
+
+        Self { cdf }
+    }
+
+    /// Draws a key rank
+    fn sample(&self, rng: &mut ChaCha8Rng) -> usize {
+        let p: f64 = rng.gen();
+
+        self.cdf.partition_point(|&c| c < p).min(self.cdf.len() - 1)
+    }
+}
+
+/// Workload with its samplers built
+#[derive(Clone, Debug)]
+enum Source {
+    Uniform { keys: usize },
+    Zipf(Zipf),
+    ScanHeavy { zipf: Zipf, scan_every: usize, scan_length: usize },
+    Loop { keys: usize },
+    ShiftingHotset { keys: usize, hot_keys: usize, hot_probability: f64, shift_every: usize },
+    Phases(Vec<(Source, usize)>)
+}
+
+impl Source {
+    fn new(workload: &Workload) -> Self {
+        match workload {
+            Workload::Uniform { keys } => Self::Uniform { keys: (*keys).max(1) },
+            Workload::Zipf { keys, exponent } => Self::Zipf(Zipf::new(*keys, *exponent)),
+            Workload::ScanHeavy { keys, exponent, scan_every, scan_length } => Self::ScanHeavy {
+                zipf: Zipf::new(*keys, *exponent),
+                scan_every: *scan_every,
+                scan_length: *scan_length
+            },
+            Workload::Loop { keys } => Self::Loop { keys: (*keys).max(1) },
+            Workload::ShiftingHotset { keys, hot_keys, hot_probability, shift_every } => Self::ShiftingHotset {
+                keys: (*keys).max(1),
+                hot_keys: (*hot_keys).clamp(1, (*keys).max(1)),
+                hot_probability: *hot_probability,
+                shift_every: (*shift_every).max(1)
+            },
+            Workload::Phases(phases) => Self::Phases(phases
+                .iter()
+                .filter(|(_, length)| *length > 0)
+                .map(|(workload, length)| (Self::new(workload), *length))
+                .collect()
+            )
+        }
+    }
+
+    /// Returns the key requested at the given position of the phase
+    fn next_key(&self, rng: &mut ChaCha8Rng, position: usize, scanned: &mut u64) -> String {
+        match self {
+            Self::Uniform { keys } => rng.gen_range(0..*keys).to_string(),
+            Self::Zipf(zipf) => zipf.sample(rng).to_string(),
+            Self::ScanHeavy { zipf, scan_every, scan_length } => {
+                let period = scan_every + scan_length;
+                if *scan_length == 0 || position % period < *scan_every {
+                    zipf.sample(rng).to_string()
+                } else {
+                    *scanned += 1;
+                    format!("scan:{}", scanned)
+                }
+            },
+            Self::Loop { keys } => (position % keys).to_string(),
+            Self::ShiftingHotset { keys, hot_keys, hot_probability, shift_every } => {
+                let offset = (position / shift_every) * hot_keys;
+                let key = if rng.gen_bool(hot_probability.clamp(0.0, 1.0)) {
+                    offset + rng.gen_range(0..*hot_keys)
+                } else {
+                    rng.gen_range(0..*keys)
+                };
+
+                (key % keys).to_string()
+            },
+            Self::Phases(phases) => {
+                let cycle: usize = phases.iter().map(|(_, length)| length).sum();
+                let mut position = position % cycle.max(1);
+                for (source, length) in phases {
+                    if position < *length {
+                        return source.next_key(rng, position, scanned);
+                    }
+                    position -= length;
+                }
+
+                String::new()
+            }
+        }
+    }
+}
+
+/// Infinite, seeded stream of accesses for a workload
+/// Every access is a read of size 1 timestamped with its position
+#[derive(Clone, Debug)]
+pub struct WorkloadGenerator {
+    rng: ChaCha8Rng,
+    source: Source,
+    position: usize,
+    scanned: u64
+}
+
+impl Iterator for WorkloadGenerator {
+    type Item = Access;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Source::Phases(phases) = &self.source {
+            if phases.is_empty() {
+                return None;
+            }
+        }
+
+        let key = self.source.next_key(&mut self.rng, self.position, &mut self.scanned);
+        let access = Access { key, op: Op::Get, size: 1, timestamp: self.position as u64 };
+        self.position += 1;
+
+        Some(access)
+    }
+}